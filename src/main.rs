@@ -1,12 +1,13 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    spec::Specification,
-    subcommands::{Generate, Print},
+    spec::{ParamStructure, Specification},
+    subcommands::{Generate, Print, Verify},
 };
 
 mod spec;
@@ -29,6 +30,8 @@ enum Subcommands {
     Generate(Generate),
     #[clap(about = "Print the spec to standard output")]
     Print(Print),
+    #[clap(about = "Check schemas derived from generated types against the spec")]
+    Verify(Verify),
 }
 
 #[derive(Debug, Clone)]
@@ -59,15 +62,47 @@ struct RawSpecs {
     ws: Option<&'static str>,
 }
 
+/// Specification file a method is defined in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpecFile {
+    Main,
+    Write,
+    Trace,
+    Ws,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ProfileOptions {
     flatten_options: FlattenOption,
     ignore_types: Vec<String>,
     allow_unknown_field_types: Vec<String>,
+    /// Types keeping unknown fields in an `extra` map so that they're serialized back.
+    #[serde(default)]
+    capture_unknown_field_types: Vec<String>,
+    #[serde(default)]
+    extra_ref_types: ExtraRefTypeOptions,
+    #[serde(default)]
+    builder_types: BuilderTypeOptions,
     fixed_field_types: FixedFieldsOptions,
+    /// Fields and enum variants wrapped in `OwnedPtr` even when not boxed automatically.
     arc_wrapped_types: ArcWrappingOptions,
+    /// Fields and enum variants never boxed automatically, even when recursive or large. Variant
+    /// sizes are upper bounds that only account for the niches of `OwnedPtr` and `NonZero`
+    /// integers, so this also keeps variants the estimate overstates unboxed.
+    #[serde(default)]
+    unboxed_types: UnboxingOptions,
     additional_derives_types: AdditionalDerivesOptions,
+    #[serde(default)]
+    inline_type_names: InlineTypeNamingOptions,
+    #[serde(default)]
+    version_offsets: VersionOffsetOptions,
+    #[serde(default)]
+    open_enum_types: OpenEnumOptions,
+    #[serde(default)]
+    param_structures: ParamStructureOptions,
+    #[serde(default)]
+    borrowed_types: BorrowedTypeOptions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,11 +115,51 @@ struct ArcWrappingOptions {
     arc_wrapped_types: Vec<RustTypeWithArcWrappedFields>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UnboxingOptions {
+    unboxed_types: Vec<RustTypeWithUnboxedFields>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AdditionalDerivesOptions {
     additional_derives_types: Vec<RustTypesWithAdditionalDerives>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct InlineTypeNamingOptions {
+    inline_type_names: Vec<InlineTypeName>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct VersionOffsetOptions {
+    version_offsets: Vec<VersionOffset>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct OpenEnumOptions {
+    open_enum_types: Vec<OpenEnum>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ParamStructureOptions {
+    param_structures: Vec<MethodParamStructure>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ExtraRefTypeOptions {
+    extra_ref_types: Vec<ExtraRefType>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BorrowedTypeOptions {
+    borrowed_types: Vec<BorrowedType>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BuilderTypeOptions {
+    builder_types: Vec<BuilderType>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct RustTypeWithFixedFields {
     name: String,
@@ -97,21 +172,100 @@ struct RustTypeWithArcWrappedFields {
     fields: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RustTypeWithUnboxedFields {
+    name: String,
+    fields: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct RustTypesWithAdditionalDerives {
     name: String,
     derives: Vec<String>,
 }
 
+/// Name override for a type synthesized from an anonymous schema used inline as a field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InlineTypeName {
+    name: String,
+    field: String,
+    type_name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FixedField {
     name: String,
     value: String,
-    is_query_version: bool,
+    /// Names of offsets that can be added to `value`, each recorded in its own flag field.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    version_offsets: Vec<String>,
     #[serde(default)]
     must_present_in_deser: bool,
 }
 
+/// String enum keeping values not defined in the specification in an `Unknown` variant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenEnum {
+    name: String,
+    #[serde(default)]
+    non_exhaustive: bool,
+}
+
+/// Param structure of a method overriding the one declared in the specification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MethodParamStructure {
+    method: String,
+    param_structure: ParamStructure,
+}
+
+/// Type also getting a `Ref` variant with borrowed fields, for serializing without cloning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExtraRefType {
+    name: String,
+}
+
+/// Struct also getting a `Borrowed` variant that deserializes without copying strings out of the
+/// input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BorrowedType {
+    name: String,
+    /// Fields kept as `&RawValue` to be parsed lazily, which requires the `raw_value` feature of
+    /// `serde_json`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    raw_fields: Vec<String>,
+}
+
+/// Struct also getting a builder, which takes required fields up front and sets the others through
+/// setters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BuilderType {
+    name: String,
+    /// Required fields set through setters too, starting out with a default value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    defaults: Vec<BuilderDefault>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BuilderDefault {
+    field: String,
+    /// Rust expression of the default value.
+    value: String,
+}
+
+/// Offset that can be added to the value of a fixed field, such as the one marking query-only
+/// transaction versions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VersionOffset {
+    /// Name of the generated `Felt` constant.
+    name: String,
+    /// Raw limbs of the offset as taken by `Felt::from_raw`.
+    raw: [u64; 4],
+    /// Name of the `bool` field set when the offset is applied.
+    flag: String,
+    /// Doc comment of the flag field.
+    description: String,
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 enum FlattenOption {
@@ -138,6 +292,15 @@ impl FromStr for SpecVersion {
     }
 }
 
+impl Display for SpecVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => write!(f, "{}", value.get_name()),
+            None => write!(f, "{self:?}"),
+        }
+    }
+}
+
 impl ValueEnum for SpecVersion {
     fn value_variants<'a>() -> &'a [Self] {
         &[
@@ -228,6 +391,27 @@ impl RawSpecs {
 
         Ok(specs)
     }
+
+    /// Maps method names to the specification file defining them.
+    pub fn method_sources(&self) -> Result<IndexMap<String, SpecFile>> {
+        let mut sources = IndexMap::new();
+
+        for (file, raw) in [
+            (SpecFile::Main, Some(self.main)),
+            (SpecFile::Write, Some(self.write)),
+            (SpecFile::Trace, Some(self.trace)),
+            (SpecFile::Ws, self.ws),
+        ] {
+            if let Some(raw) = raw {
+                let specs: Specification = serde_json::from_str(raw)?;
+                for method in specs.methods.into_iter() {
+                    sources.entry(method.name).or_insert(file);
+                }
+            }
+        }
+
+        Ok(sources)
+    }
 }
 
 impl FixedFieldsOptions {
@@ -257,6 +441,18 @@ impl ArcWrappingOptions {
     }
 }
 
+impl UnboxingOptions {
+    fn is_field_unboxed(&self, type_name: &str, field_name: &str) -> bool {
+        self.unboxed_types.iter().any(|item| {
+            if item.name == type_name {
+                item.fields.iter().any(|field| field == field_name)
+            } else {
+                false
+            }
+        })
+    }
+}
+
 impl AdditionalDerivesOptions {
     fn find_additional_derives(&self, type_name: &str) -> Option<Vec<String>> {
         self.additional_derives_types.iter().find_map(|item| {
@@ -269,6 +465,72 @@ impl AdditionalDerivesOptions {
     }
 }
 
+impl VersionOffsetOptions {
+    fn find_version_offset(&self, name: &str) -> Option<VersionOffset> {
+        self.version_offsets
+            .iter()
+            .find(|item| item.name == name)
+            .cloned()
+    }
+}
+
+impl OpenEnumOptions {
+    fn find_open_enum(&self, type_name: &str) -> Option<OpenEnum> {
+        self.open_enum_types
+            .iter()
+            .find(|item| item.name == type_name)
+            .cloned()
+    }
+}
+
+impl ParamStructureOptions {
+    fn find_param_structure(&self, method: &str) -> Option<ParamStructure> {
+        self.param_structures
+            .iter()
+            .find(|item| item.method == method)
+            .map(|item| item.param_structure)
+    }
+}
+
+impl ExtraRefTypeOptions {
+    fn find_extra_ref_type(&self, type_name: &str) -> Option<ExtraRefType> {
+        self.extra_ref_types
+            .iter()
+            .find(|item| item.name == type_name)
+            .cloned()
+    }
+}
+
+impl BorrowedTypeOptions {
+    fn find_borrowed_type(&self, type_name: &str) -> Option<BorrowedType> {
+        self.borrowed_types
+            .iter()
+            .find(|item| item.name == type_name)
+            .cloned()
+    }
+}
+
+impl BuilderTypeOptions {
+    fn find_builder_type(&self, type_name: &str) -> Option<BuilderType> {
+        self.builder_types
+            .iter()
+            .find(|item| item.name == type_name)
+            .cloned()
+    }
+}
+
+impl InlineTypeNamingOptions {
+    fn find_type_name(&self, type_name: &str, field_name: &str) -> Option<String> {
+        self.inline_type_names.iter().find_map(|item| {
+            if item.name == type_name && item.field == field_name {
+                Some(item.type_name.clone())
+            } else {
+                None
+            }
+        })
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -377,6 +639,7 @@ fn main() {
     let result = match cli.command {
         Subcommands::Generate(cmd) => cmd.run(&profiles),
         Subcommands::Print(cmd) => cmd.run(&profiles),
+        Subcommands::Verify(cmd) => cmd.run(&profiles),
     };
 
     result.expect("Error running commmand");
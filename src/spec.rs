@@ -27,7 +27,7 @@ pub struct Method {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub param_structure: Option<String>,
+    pub param_structure: Option<ParamStructure>,
     pub params: Vec<Param>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<MethodResult>,
@@ -35,6 +35,16 @@ pub struct Method {
     pub errors: Option<Vec<Reference>>,
 }
 
+/// How the params of a method are passed, either as an object or as an array.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParamStructure {
+    ByName,
+    ByPosition,
+    #[default]
+    Either,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Components {
@@ -79,6 +89,7 @@ pub enum Schema {
     OneOf(OneOf),
     AllOf(AllOf),
     Primitive(Primitive),
+    Constraint(Constraint),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -114,7 +125,7 @@ pub struct AllOf {
     pub description: Option<String>,
     pub all_of: Vec<Schema>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub additional_properties: Option<bool>,
+    pub additional_properties: Option<AdditionalProperties>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -125,6 +136,22 @@ pub enum Primitive {
     Integer(IntegerPrimitive),
     Object(ObjectPrimitive),
     String(StringPrimitive),
+    Null(NullPrimitive),
+}
+
+/// Schema constraining values without declaring a type, as used in `not`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Constraint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "const")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -144,6 +171,9 @@ pub struct BooleanPrimitive {
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(rename = "const")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -154,10 +184,20 @@ pub struct IntegerPrimitive {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub minimum: Option<i32>,
-    // Field not handled for now
+    pub minimum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub not: Option<serde_json::Value>,
+    pub format: Option<String>,
+    #[serde(rename = "const")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<Schema>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -169,13 +209,25 @@ pub struct ObjectPrimitive {
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub properties: IndexMap<String, Schema>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub required: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub additional_properties: Option<bool>,
-    // Field not handled for now
+    pub additional_properties: Option<AdditionalProperties>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub pattern_properties: IndexMap<String, Schema>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub not: Option<serde_json::Value>,
+    pub property_names: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<Schema>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+    Allowed(bool),
+    Schema(Box<Schema>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -190,14 +242,26 @@ pub struct StringPrimitive {
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub r#enum: Option<Vec<String>>,
+    #[serde(rename = "const")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct NullPrimitive {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ErrorType {
-    Error(Error),
+    Error(Box<Error>),
     Reference(Reference),
 }
 
@@ -217,6 +281,7 @@ impl Schema {
             Self::OneOf(schema) => schema.title.as_ref(),
             Self::AllOf(schema) => schema.title.as_ref(),
             Self::Primitive(schema) => schema.title(),
+            Self::Constraint(schema) => schema.title.as_ref(),
         }
     }
 
@@ -226,6 +291,7 @@ impl Schema {
             Self::OneOf(schema) => schema.description.as_ref(),
             Self::AllOf(schema) => schema.description.as_ref(),
             Self::Primitive(schema) => schema.description(),
+            Self::Constraint(schema) => schema.description.as_ref(),
         }
     }
 
@@ -235,6 +301,40 @@ impl Schema {
             Self::OneOf(_) => None,
             Self::AllOf(_) => None,
             Self::Primitive(schema) => schema.summary(),
+            Self::Constraint(_) => None,
+        }
+    }
+
+    /// Gets the constant value of the schema, if it only allows a single value.
+    pub fn const_value(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::Primitive(Primitive::Boolean(schema)) => schema.const_value.map(Into::into),
+            Self::Primitive(Primitive::Integer(schema)) => schema.const_value.map(Into::into),
+            Self::Primitive(Primitive::String(schema)) => schema
+                .const_value
+                .as_ref()
+                .map(|value| value.as_str().into()),
+            Self::Constraint(schema) => schema.const_value.clone(),
+            _ => None,
+        }
+    }
+
+    /// Gets the non-null option of `oneOf` unions with `null`, like `[{ "$ref": ... }, { "type":
+    /// "null" }]`.
+    pub fn non_null_option(&self) -> Option<&Schema> {
+        let Self::OneOf(one_of) = self else {
+            return None;
+        };
+
+        let (nulls, options): (Vec<_>, Vec<_>) = one_of
+            .one_of
+            .iter()
+            .partition(|option| matches!(option, Self::Primitive(Primitive::Null(_))));
+
+        if !nulls.is_empty() && options.len() == 1 {
+            Some(options[0])
+        } else {
+            None
         }
     }
 }
@@ -247,6 +347,7 @@ impl Primitive {
             Self::Integer(schema) => schema.title.as_ref(),
             Self::Object(schema) => schema.title.as_ref(),
             Self::String(schema) => schema.title.as_ref(),
+            Self::Null(schema) => schema.title.as_ref(),
         }
     }
 
@@ -257,6 +358,7 @@ impl Primitive {
             Self::Integer(schema) => schema.description.as_ref(),
             Self::Object(schema) => schema.description.as_ref(),
             Self::String(schema) => schema.description.as_ref(),
+            Self::Null(schema) => schema.description.as_ref(),
         }
     }
 
@@ -267,6 +369,70 @@ impl Primitive {
             Self::Integer(_) => None,
             Self::Object(schema) => schema.summary.as_ref(),
             Self::String(_) => None,
+            Self::Null(_) => None,
+        }
+    }
+}
+
+impl IntegerPrimitive {
+    /// Gets the inclusive lower bound, if any.
+    pub fn lower_bound(&self) -> Option<i128> {
+        match (
+            self.minimum.map(i128::from),
+            self.exclusive_minimum.map(i128::from),
+        ) {
+            (Some(minimum), Some(exclusive_minimum)) => Some(minimum.max(exclusive_minimum + 1)),
+            (Some(minimum), None) => Some(minimum),
+            (None, Some(exclusive_minimum)) => Some(exclusive_minimum + 1),
+            (None, None) => None,
+        }
+    }
+
+    /// Gets the inclusive upper bound, if any.
+    pub fn upper_bound(&self) -> Option<i128> {
+        match (
+            self.maximum.map(i128::from),
+            self.exclusive_maximum.map(i128::from),
+        ) {
+            (Some(maximum), Some(exclusive_maximum)) => Some(maximum.min(exclusive_maximum - 1)),
+            (Some(maximum), None) => Some(maximum),
+            (None, Some(exclusive_maximum)) => Some(exclusive_maximum - 1),
+            (None, None) => None,
+        }
+    }
+
+    /// Gets the value excluded with `{ "not": { "const": <value> } }`, if any.
+    pub fn excluded_value(&self) -> Option<i128> {
+        self.not.as_ref()?.const_value()?.as_i64().map(i128::from)
+    }
+}
+
+impl ObjectPrimitive {
+    /// Gets the schemas of values in map-like objects, which define no fixed properties but
+    /// describe their entries with `additionalProperties` or `patternProperties`.
+    pub fn map_value_schemas(&self) -> Option<Vec<&Schema>> {
+        if !self.properties.is_empty() {
+            return None;
+        }
+
+        let mut value_schemas: Vec<&Schema> = self.pattern_properties.values().collect();
+        if let Some(AdditionalProperties::Schema(schema)) = &self.additional_properties {
+            value_schemas.push(schema);
+        }
+
+        if value_schemas.is_empty() {
+            None
+        } else {
+            Some(value_schemas)
+        }
+    }
+
+    /// Gets the fields excluded with `{ "not": { "required": [...] } }`, which must not all be
+    /// present at the same time.
+    pub fn excluded_fields(&self) -> &[String] {
+        match self.not.as_deref() {
+            Some(Schema::Constraint(constraint)) => &constraint.required,
+            _ => &[],
         }
     }
 }
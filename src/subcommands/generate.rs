@@ -1,29 +1,85 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use regex::Regex;
 
 use crate::{
-    built_info, spec::*, AdditionalDerivesOptions, ArcWrappingOptions, FixedField,
-    FixedFieldsOptions, FlattenOption, GenerationProfile, SpecVersion,
+    built_info, spec::*, BuilderDefault, FixedField, FlattenOption, GenerationProfile,
+    InlineTypeNamingOptions, OpenEnum, ProfileOptions, SpecFile, SpecVersion, VersionOffset,
 };
 
 #[derive(Debug, Parser)]
 pub struct Generate {
     #[clap(long, env, help = "Version of the specification")]
     spec: SpecVersion,
+    #[clap(
+        long,
+        help = "Check string patterns from the specification on deserialization behind the \
+                `validate_patterns` feature (requires `std` and `regex`)"
+    )]
+    validate_patterns: bool,
+    #[clap(
+        long,
+        requires = "validate_patterns",
+        help = "Generate `Arbitrary` impls behind the `arbitrary` feature, producing values that \
+                serialize to valid JSON (requires `--validate-patterns`)"
+    )]
+    arbitrary: bool,
+    #[clap(
+        long,
+        help = "Generate a test module checking serde round trips of generated types on sample JSON"
+    )]
+    serde_tests: bool,
+    #[clap(
+        long,
+        help = "Derive `schemars::JsonSchema` behind the `schemars` feature, along with a \
+                `spec_schemas()` function for the `verify` command"
+    )]
+    json_schema: bool,
 }
 
 const MAX_LINE_LENGTH: usize = 100;
+/// Width of call arguments beyond which rustfmt breaks them, even when the line would fit.
+const FN_CALL_WIDTH: usize = 60;
+/// Width of attribute arguments beyond which rustfmt puts each argument on its own line.
+const ATTR_FN_LIKE_WIDTH: usize = 70;
+/// Width of method chains beyond which rustfmt puts each call on its own line.
+const CHAIN_WIDTH: usize = 60;
+/// Width of array literals beyond which rustfmt puts each element on its own line.
+const ARRAY_WIDTH: usize = 60;
+
+/// Size difference between the two largest variants of an enum from which the largest one gets
+/// boxed, same as the default threshold of the `large_enum_variant` lint.
+const LARGE_VARIANT_SIZE_DIFFERENCE: usize = 200;
+
+/// Size of `OwnedPtr` and of the discriminant of enums in size estimates.
+const POINTER_SIZE: usize = 8;
 
 #[derive(Debug, Clone)]
 struct TypeResolutionResult {
     model_types: Vec<RustType>,
     aliases: Vec<RustAlias>,
     request_response_types: Vec<RustType>,
+    /// Result types of methods that have results, keyed by method name
+    method_results: IndexMap<String, RustFieldType>,
     not_implemented: Vec<String>,
+    patterns: Vec<PatternRule>,
+}
+
+/// Method information from the specification, rendered as a table for runtime use.
+#[derive(Debug, Clone)]
+struct MethodMetadata {
+    name: String,
+    /// Name of the `Method` variant, also used for the request type name
+    variant_name: String,
+    summary: String,
+    params: Vec<(String, bool)>,
+    param_structure: ParamStructure,
+    result_type: Option<String>,
+    error_codes: Vec<u32>,
+    source: SpecFile,
 }
 
 #[derive(Debug, Clone)]
@@ -62,27 +118,48 @@ struct RustAliasContent {
 #[derive(Debug, Clone)]
 struct RustStruct {
     allow_unknown_fields: bool,
-    serde_as_obj: bool,
+    /// Set for method params, which are (de)serialized as an object or an array accordingly.
+    param_structure: Option<ParamStructure>,
     extra_ref_type: bool,
+    /// Defaults of required fields left out of the builder constructor, when a builder is
+    /// generated.
+    builder_defaults: Option<Vec<BuilderDefault>>,
+    /// Fields of the `Borrowed` variant, when one is generated.
+    borrowed_fields: Option<Vec<BorrowedField>>,
     fields: Vec<RustField>,
+    /// Fields that must not all be present at the same time.
+    excluded_fields: Vec<String>,
+    /// Offsets used by fixed fields, each adding a flag field to the struct.
+    version_offsets: Vec<VersionOffset>,
     derives: Vec<String>,
 }
 
+/// Field of a `Borrowed` variant, which gets `#[serde(borrow)]` when it borrows from the input.
+#[derive(Debug, Clone)]
+struct BorrowedField {
+    field: RustField,
+    borrow: bool,
+}
+
 #[derive(Debug, Clone)]
 struct RustEnum {
     is_error: bool,
     variants: Vec<RustVariant>,
+    /// Set for string enums with a catch-all variant for values unknown to the specification.
+    open: Option<OpenEnum>,
     derives: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 struct RustWrapper {
     type_name: String,
+    serializer: Option<SerializerOverride>,
 }
 
 #[derive(Debug, Clone)]
 struct RustUnit {
-    serde_as_obj: bool,
+    /// Set for method params, which are (de)serialized as an object or an array accordingly.
+    param_structure: Option<ParamStructure>,
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +169,9 @@ struct RustField {
     optional: bool,
     fixed: Option<FixedField>,
     arc_wrap: bool,
+    /// Wrapped in `OwnedPtr` by the generator to break recursion, (de)serialized through
+    /// `serde_with` instead of a handwritten `Owned` adapter.
+    auto_boxed: bool,
     type_name: String,
     serde_rename: Option<String>,
     serde_flatten: bool,
@@ -106,6 +186,8 @@ struct RustVariant {
     error_text: Option<String>,
     error_code: Option<u32>,
     wraps: Option<RustFieldType>,
+    /// Whether the wrapped type is held in an `OwnedPtr`.
+    boxed: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -114,12 +196,215 @@ struct RustFieldType {
     serializer: Option<SerializerOverride>,
 }
 
+impl RustFieldType {
+    /// Expression deserializing a value of this type from the `&serde_json::Value` in `var`.
+    fn data_decoding_expr(&self, var: &str) -> String {
+        match &self.serializer {
+            Some(SerializerOverride::Serde(adapter)) => {
+                format!("{}::deserialize({var})", adapter.module)
+            }
+            Some(SerializerOverride::SerdeAs(serde_as)) => {
+                format!("serde_with::As::<{serde_as}>::deserialize({var})")
+            }
+            None if self.type_name.contains('<') => {
+                format!("<{}>::deserialize({var})", self.type_name)
+            }
+            None => format!("{}::deserialize({var})", self.type_name),
+        }
+    }
+
+    /// Expression serializing the value of this type referenced by `var` into a
+    /// `serde_json::Value`.
+    fn data_encoding_expr(&self, var: &str) -> String {
+        match &self.serializer {
+            Some(SerializerOverride::Serde(adapter)) => format!(
+                "{}::serialize({var}, serde_json::value::Serializer)",
+                adapter.module
+            ),
+            Some(SerializerOverride::SerdeAs(serde_as)) => format!(
+                "serde_json::to_value(serde_with::ser::SerializeAsWrap::<{}, {}>::new({var}))",
+                self.type_name, serde_as
+            ),
+            None => format!("serde_json::to_value({var})"),
+        }
+    }
+
+    /// Expression generating an arbitrary value of this type out of the `Unstructured` in `u`,
+    /// using the `ArbitraryAs` impl of the serializer to keep values valid.
+    fn arbitrary_expr(&self) -> String {
+        match &self.serializer {
+            // JSON values aren't `Arbitrary`, and their schemas are unknown anyway
+            _ if self.type_name.contains("serde_json::Value") => String::from("Default::default()"),
+            Some(SerializerOverride::SerdeAs(serde_as)) if serde_as.contains('<') => {
+                format!("<{serde_as}>::arbitrary_as(u)?")
+            }
+            Some(SerializerOverride::SerdeAs(serde_as)) => format!("{serde_as}::arbitrary_as(u)?"),
+            Some(SerializerOverride::Serde(_)) | None => String::from("u.arbitrary()?"),
+        }
+    }
+
+    /// Expression serializing the value of this type referenced by `var` into `serializer`.
+    fn serializing_expr(&self, var: &str, serializer: &str) -> String {
+        match &self.serializer {
+            Some(SerializerOverride::Serde(adapter)) => {
+                format!("{}::serialize({var}, {serializer})", adapter.module)
+            }
+            Some(SerializerOverride::SerdeAs(serde_as)) => {
+                format!("serde_with::As::<{serde_as}>::serialize({var}, {serializer})")
+            }
+            None => format!("{var}.serialize({serializer})"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 enum SerializerOverride {
-    Serde(String),
+    Serde(SerdeWithAdapter),
     SerdeAs(String),
 }
 
+/// A `#[serde(with)]` module that can be composed into `Option` and `Vec` positions through a
+/// generated `serde_as` adapter.
+#[derive(Debug, Clone, Copy)]
+struct SerdeWithAdapter {
+    module: &'static str,
+    adapter: &'static str,
+    type_name: &'static str,
+}
+
+/// Anonymous schemas used in field positions, for which named types are synthesized.
+struct InlineTypes<'a> {
+    naming: &'a InlineTypeNamingOptions,
+    reserved_names: HashSet<String>,
+    registered: IndexMap<String, Schema>,
+    pending: VecDeque<String>,
+}
+
+/// State shared across type resolution.
+struct ResolutionContext<'a> {
+    inline_types: InlineTypes<'a>,
+    /// Only present when pattern validation is enabled.
+    patterns: Option<PatternRegistry>,
+}
+
+/// String patterns from the specification to be checked on deserialization.
+struct PatternRegistry {
+    /// Patterns of named schemas, resolved through references.
+    schema_patterns: HashMap<String, PatternRule>,
+    /// Maps generated type names back to schema names.
+    schema_names: HashMap<String, String>,
+    /// Patterns referenced by generated code, keyed by marker type name.
+    used: IndexMap<String, PatternRule>,
+}
+
+/// A string pattern, rendered as a marker type implementing `SpecPattern`.
+#[derive(Debug, Clone)]
+struct PatternRule {
+    marker: String,
+    rule: String,
+    pattern: String,
+}
+
+/// Builds sample JSON of generated types for the generated serde tests.
+struct SampleSynthesizer<'a> {
+    types: HashMap<&'a str, &'a RustType>,
+    aliases: HashMap<&'a str, &'a str>,
+    /// Patterns keyed by marker type name, for pattern-validated strings.
+    patterns: HashMap<&'a str, &'a str>,
+    samples: HashMap<String, Option<serde_json::Value>>,
+    /// Types being sampled, which can't be nested into their own samples.
+    visiting: HashSet<String>,
+}
+
+/// A Rust integer type that can be chosen for `integer` schemas.
+#[derive(Debug, Clone, Copy)]
+struct IntegerType {
+    type_name: &'static str,
+    non_zero_type_name: &'static str,
+    format: &'static str,
+    min: i128,
+    max: i128,
+}
+
+/// Integer types in order of preference.
+const INTEGER_TYPES: [IntegerType; 8] = [
+    IntegerType {
+        type_name: "u8",
+        non_zero_type_name: "core::num::NonZeroU8",
+        format: "uint8",
+        min: 0,
+        max: u8::MAX as i128,
+    },
+    IntegerType {
+        type_name: "u16",
+        non_zero_type_name: "core::num::NonZeroU16",
+        format: "uint16",
+        min: 0,
+        max: u16::MAX as i128,
+    },
+    IntegerType {
+        type_name: "u32",
+        non_zero_type_name: "core::num::NonZeroU32",
+        format: "uint32",
+        min: 0,
+        max: u32::MAX as i128,
+    },
+    IntegerType {
+        type_name: "u64",
+        non_zero_type_name: "core::num::NonZeroU64",
+        format: "uint64",
+        min: 0,
+        max: u64::MAX as i128,
+    },
+    IntegerType {
+        type_name: "i8",
+        non_zero_type_name: "core::num::NonZeroI8",
+        format: "int8",
+        min: i8::MIN as i128,
+        max: i8::MAX as i128,
+    },
+    IntegerType {
+        type_name: "i16",
+        non_zero_type_name: "core::num::NonZeroI16",
+        format: "int16",
+        min: i16::MIN as i128,
+        max: i16::MAX as i128,
+    },
+    IntegerType {
+        type_name: "i32",
+        non_zero_type_name: "core::num::NonZeroI32",
+        format: "int32",
+        min: i32::MIN as i128,
+        max: i32::MAX as i128,
+    },
+    IntegerType {
+        type_name: "i64",
+        non_zero_type_name: "core::num::NonZeroI64",
+        format: "int64",
+        min: i64::MIN as i128,
+        max: i64::MAX as i128,
+    },
+];
+
+const SERDE_WITH_ADAPTERS: [SerdeWithAdapter; 1] = [SerdeWithAdapter {
+    module: "base64",
+    adapter: "Base64",
+    type_name: "Vec<u8>",
+}];
+
+/// Samples of pattern-validated strings deserialized through `UfeHex` or `NumAsHex`, which must be
+/// canonical hex strings to be serialized back unchanged.
+const HEX_PATTERN_SAMPLES: [&str; 2] = ["0x1", "0x1111111111111111111111111111111111111111"];
+
+/// Samples of other pattern-validated strings, tried in order.
+const STRING_PATTERN_SAMPLES: [&str; 5] = [
+    "0x1",
+    "0x1111111111111111111111111111111111111111",
+    "AAAA",
+    "0x01",
+    "sample",
+];
+
 impl Generate {
     pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
         let profile = profiles
@@ -153,16 +438,20 @@ impl Generate {
             println!();
         }
 
-        let result = resolve_types(
+        let result = resolve_types(&specs, &profile.options, self.validate_patterns)
+            .with_context(|| format!("Failed to resolve types of profile {}", profile.version))?;
+
+        let method_sources = profile
+            .raw_specs
+            .method_sources()
+            .expect("Failed to parse specification");
+        let methods = resolve_methods(
             &specs,
-            &profile.options.flatten_options,
-            &profile.options.ignore_types,
-            &profile.options.allow_unknown_field_types,
-            &profile.options.fixed_field_types,
-            &profile.options.arc_wrapped_types,
-            &profile.options.additional_derives_types,
+            &profile.options,
+            &method_sources,
+            &result.method_results,
         )
-        .expect("Failed to resolve types");
+        .expect("Failed to resolve methods");
 
         if !result.not_implemented.is_empty() {
             println!("// Code generation requested but not implemented for these types:");
@@ -172,6 +461,10 @@ impl Generate {
             println!();
         }
 
+        println!("// Request params are deserialized through `serde_json::value::RawValue`, which needs the");
+        println!("// `raw_value` feature of `serde_json`.");
+        println!();
+
         println!("#![allow(missing_docs)]");
         println!("#![allow(clippy::doc_markdown)]");
         println!("#![allow(clippy::missing_const_for_fn)]");
@@ -218,13 +511,50 @@ impl Generate {
         println!("type RandomState = foldhash::fast::RandomState;");
         println!();
 
-        println!("const QUERY_VERSION_OFFSET: Felt = Felt::from_raw([");
-        println!("    576460752142434320,");
-        println!("    18446744073709551584,");
-        println!("    17407,");
-        println!("    18446744073700081665,");
-        println!("]);");
-        println!();
+        for offset in profile.options.version_offsets.version_offsets.iter() {
+            println!("const {}: Felt = Felt::from_raw([", offset.name);
+            for limb in offset.raw.iter() {
+                println!("    {limb},");
+            }
+            println!("]);");
+            println!();
+        }
+
+        let all_types = result
+            .model_types
+            .iter()
+            .chain(result.request_response_types.iter())
+            .collect::<Vec<_>>();
+
+        for adapter in SERDE_WITH_ADAPTERS.iter() {
+            if adapter.is_used(&all_types) {
+                adapter.render_stdout();
+                println!();
+            }
+        }
+
+        if !result.patterns.is_empty() {
+            render_pattern_validation_stdout();
+            println!();
+
+            for pattern in result.patterns.iter() {
+                pattern.render_stdout();
+                println!();
+            }
+        }
+
+        if is_serde_as_adapter_used(&all_types, "Bounded") {
+            render_bounded_adapter_stdout();
+            println!();
+        }
+
+        if all_types.iter().any(|rust_type| match &rust_type.content {
+            RustTypeKind::Enum(content) => content.is_string_enum() && content.open.is_none(),
+            _ => false,
+        }) {
+            render_parse_enum_error_stdout();
+            println!();
+        }
 
         let mut manual_serde_types = vec![];
 
@@ -236,20 +566,28 @@ impl Generate {
             println!();
         }
 
-        for rust_type in result
-            .model_types
-            .iter()
-            .chain(result.request_response_types.iter())
-        {
+        for rust_type in all_types.iter() {
             if rust_type.need_custom_serde() {
                 manual_serde_types.push(rust_type);
             }
 
-            rust_type.render_stdout();
+            rust_type.render_stdout(self.json_schema);
 
             println!();
         }
 
+        render_method_table_stdout(&methods);
+        println!();
+
+        render_request_data_stdout(&methods);
+        println!();
+
+        render_response_data_stdout(&methods, &result.method_results);
+        println!();
+
+        render_json_rpc_envelope_stdout();
+        println!();
+
         for (ind, rust_type) in manual_serde_types.iter().enumerate() {
             rust_type.render_serde_stdout();
 
@@ -258,12 +596,45 @@ impl Generate {
             }
         }
 
+        if self.json_schema {
+            if is_serde_as_adapter_used(&all_types, "MerkleNodeMap") {
+                println!();
+                render_merkle_node_map_entry_stdout();
+            }
+
+            let samples = SampleSynthesizer::new(&all_types, &result.aliases, &result.patterns);
+            for rust_type in manual_serde_types.iter() {
+                println!();
+                rust_type
+                    .render_json_schema_stdout(&samples)
+                    .expect("Failed to render JSON schemas");
+            }
+
+            println!();
+            render_spec_schemas_stdout(&specs, &all_types, &result.aliases);
+        }
+
+        if self.arbitrary {
+            println!();
+            render_arbitrary_as_stdout(&all_types, !result.patterns.is_empty());
+
+            for rust_type in all_types.iter() {
+                println!();
+                rust_type.render_arbitrary_stdout();
+            }
+        }
+
+        if self.serde_tests {
+            println!();
+            render_serde_tests_stdout(&all_types, &result.aliases, &result.patterns);
+        }
+
         Ok(())
     }
 }
 
 impl RustType {
-    pub fn render_stdout(&self) {
+    pub fn render_stdout(&self, json_schema: bool) {
         match (self.title.as_ref(), self.description.as_ref()) {
             (Some(title), Some(description)) => {
                 print_doc(title, 0);
@@ -279,14 +650,74 @@ impl RustType {
             (None, None) => {}
         }
 
-        self.content.render_stdout(&self.name);
+        self.content.render_stdout(&self.name, json_schema);
+    }
+
+    pub fn render_arbitrary_stdout(&self) {
+        println!("#[cfg(feature = \"arbitrary\")]");
+        println!("impl<'a> arbitrary::Arbitrary<'a> for {} {{", self.name);
+
+        match &self.content {
+            RustTypeKind::Struct(content) => content.render_arbitrary_fn_stdout(),
+            RustTypeKind::Enum(content) => content.render_arbitrary_fn_stdout(),
+            RustTypeKind::Wrapper(content) => content.render_arbitrary_fn_stdout(),
+            RustTypeKind::Unit(_) => {
+                print_arbitrary_fn_signature(false);
+                println!("        Ok(Self)");
+                println!("    }}");
+            }
+        }
+
+        println!("}}");
+    }
+
+    pub fn render_json_schema_stdout(&self, samples: &SampleSynthesizer) -> Result<()> {
+        println!("#[cfg(feature = \"schemars\")]");
+        println!("impl schemars::JsonSchema for {} {{", self.name);
+        println!("    fn schema_name() -> alloc::borrow::Cow<'static, str> {{");
+        println!("        \"{}\".into()", self.name);
+        println!("    }}");
+        println!();
+
+        match &self.content {
+            RustTypeKind::Struct(content) => content.render_json_schema_fn_stdout(samples),
+            RustTypeKind::Enum(content) => content.render_json_schema_fn_stdout(),
+            RustTypeKind::Unit(content) => content.render_json_schema_fn_stdout(),
+            RustTypeKind::Wrapper(_) => anyhow::bail!(
+                "Schema impls only implemented for structs, enums and unit, not wrapper {}",
+                self.name
+            ),
+        }
+
+        println!("}}");
+
+        Ok(())
     }
 
     pub fn render_serde_stdout(&self) {
         match &self.content {
             RustTypeKind::Struct(content) => content.render_serde_stdout(&self.name),
             RustTypeKind::Unit(content) => content.render_serde_stdout(&self.name),
-            _ => todo!("serde blocks only implemented for structs and unit"),
+            RustTypeKind::Enum(content) => content.render_serde_stdout(&self.name),
+            _ => todo!("serde blocks only implemented for structs, enums and unit"),
+        }
+    }
+
+    pub fn serializers(&self) -> Vec<&SerializerOverride> {
+        match &self.content {
+            RustTypeKind::Struct(content) => content
+                .fields
+                .iter()
+                .filter_map(|field| field.serializer.as_ref())
+                .collect(),
+            RustTypeKind::Enum(content) => content
+                .variants
+                .iter()
+                .filter_map(|variant| variant.wraps.as_ref())
+                .filter_map(|wraps| wraps.serializer.as_ref())
+                .collect(),
+            RustTypeKind::Wrapper(content) => content.serializer.iter().collect(),
+            RustTypeKind::Unit(_) => vec![],
         }
     }
 
@@ -301,35 +732,269 @@ impl RustType {
 }
 
 impl RustTypeKind {
-    pub fn render_stdout(&self, name: &str) {
+    /// Adds a catch-all variant to a string enum so that unknown values can be deserialized.
+    pub fn open_enum(&mut self, name: &str, open: OpenEnum) -> Result<()> {
+        let Self::Enum(inner) = self else {
+            anyhow::bail!("Only enums can be open: {}", name);
+        };
+
+        if inner.is_error || inner.variants.iter().any(|variant| variant.wraps.is_some()) {
+            anyhow::bail!("Only string enums can be open: {}", name);
+        }
+        if inner
+            .variants
+            .iter()
+            .any(|variant| variant.name == "Unknown")
+        {
+            anyhow::bail!("Variant name `Unknown` already taken in {}", name);
+        }
+
+        inner.open = Some(open);
+
+        Ok(())
+    }
+
+    /// Adds a flattened map field collecting fields not defined in the specification.
+    pub fn capture_unknown_fields(&mut self, name: &str) -> Result<()> {
+        let Self::Struct(inner) = self else {
+            anyhow::bail!("Unknown fields can only be captured for structs: {}", name);
+        };
+
+        if inner.fields.iter().any(|field| field.name == "extra") {
+            anyhow::bail!("Field name `extra` already taken in {}", name);
+        }
+        if inner.derives.iter().any(|derive| derive == "Hash") {
+            anyhow::bail!(
+                "Types capturing unknown fields can't derive `Hash`: {}",
+                name
+            );
+        }
+
+        inner.allow_unknown_fields = true;
+        inner.fields.push(RustField {
+            description: Some("Fields not defined in the specification".into()),
+            name: "extra".into(),
+            optional: false,
+            fixed: None,
+            arc_wrap: false,
+            auto_boxed: false,
+            type_name: "IndexMap<String, serde_json::Value, RandomState>".into(),
+            serde_rename: None,
+            serde_flatten: true,
+            serializer: None,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_ref_type(&mut self, name: &str) -> Result<()> {
+        let Self::Struct(inner) = self else {
+            anyhow::bail!(
+                "Reference types can only be generated for structs: {}",
+                name
+            );
+        };
+
+        inner.extra_ref_type = true;
+
+        Ok(())
+    }
+
+    pub fn add_builder(&mut self, name: &str, defaults: &[BuilderDefault]) -> Result<()> {
+        let Self::Struct(inner) = self else {
+            anyhow::bail!("Builders can only be generated for structs: {}", name);
+        };
+
+        for default in defaults.iter() {
+            match inner
+                .fields
+                .iter()
+                .find(|field| field.name == default.field)
+            {
+                Some(field) if field.fixed.is_none() && !field.optional => {}
+                Some(_) => anyhow::bail!(
+                    "Builder default {} of {} is not a required field",
+                    default.field,
+                    name
+                ),
+                None => anyhow::bail!("Builder default {} not found in {}", default.field, name),
+            }
+        }
+
+        inner.builder_defaults = Some(defaults.to_vec());
+
+        Ok(())
+    }
+
+    /// Adds a `Borrowed` variant deserializing strings as `Cow` and `raw_fields` as `&RawValue`,
+    /// using the `Borrowed` variants of field types in `borrowed_types` too.
+    pub fn add_borrowed_type(
+        &mut self,
+        name: &str,
+        raw_fields: &[String],
+        borrowed_types: &[String],
+    ) -> Result<()> {
+        let Self::Struct(inner) = self else {
+            anyhow::bail!("Borrowed types can only be generated for structs: {}", name);
+        };
+        if inner.need_custom_serde() {
+            anyhow::bail!(
+                "Borrowed types can only be generated for structs with derived serde: {}",
+                name
+            );
+        }
+
+        for raw_field in raw_fields.iter() {
+            if !inner.fields.iter().any(|field| &field.name == raw_field) {
+                anyhow::bail!("Raw field {} not found in {}", raw_field, name);
+            }
+        }
+
+        let borrowed_fields = inner
+            .fields
+            .iter()
+            .map(|field| {
+                let borrowed_type = |type_name: &str| {
+                    if borrowed_types.iter().any(|item| item == type_name) {
+                        Some(format!("{type_name}Borrowed<'a>"))
+                    } else {
+                        None
+                    }
+                };
+
+                let (type_name, serializer) = if raw_fields.contains(&field.name) {
+                    (String::from("&'a serde_json::value::RawValue"), None)
+                } else if field.type_name == "String" && field.serializer.is_none() {
+                    (
+                        String::from("alloc::borrow::Cow<'a, str>"),
+                        Some(SerializerOverride::SerdeAs(if field.optional {
+                            String::from("Option<serde_with::BorrowCow>")
+                        } else {
+                            String::from("serde_with::BorrowCow")
+                        })),
+                    )
+                } else if let Some(type_name) = borrowed_type(&field.type_name) {
+                    (type_name, field.serializer.clone())
+                } else if let Some(type_name) = field
+                    .type_name
+                    .strip_prefix("Vec<")
+                    .and_then(|item| item.strip_suffix('>'))
+                    .and_then(borrowed_type)
+                {
+                    (format!("Vec<{type_name}>"), field.serializer.clone())
+                } else {
+                    return BorrowedField {
+                        field: field.clone(),
+                        borrow: false,
+                    };
+                };
+
+                BorrowedField {
+                    field: RustField {
+                        type_name,
+                        serializer,
+                        ..field.clone()
+                    },
+                    borrow: true,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The lifetime parameter would otherwise be unused
+        if !borrowed_fields.iter().any(|item| item.borrow) {
+            anyhow::bail!("No field to borrow in {}", name);
+        }
+
+        inner.borrowed_fields = Some(borrowed_fields);
+
+        Ok(())
+    }
+
+    /// Gets the names of types held by value, which recursion must not go through unboxed.
+    pub fn value_type_names(&self) -> Vec<String> {
+        match self {
+            Self::Struct(inner) => inner
+                .fields
+                .iter()
+                .filter(|field| !field.arc_wrap && !field.auto_boxed)
+                .filter_map(|field| get_value_type_name(&field.type_name))
+                .map(|type_name| type_name.to_owned())
+                .collect(),
+            Self::Enum(inner) => inner
+                .variants
+                .iter()
+                .filter(|variant| !variant.boxed)
+                .filter_map(|variant| variant.wraps.as_ref())
+                .filter_map(|wraps| get_value_type_name(&wraps.type_name))
+                .map(|type_name| type_name.to_owned())
+                .collect(),
+            Self::Wrapper(inner) => get_value_type_name(&inner.type_name)
+                .map(|type_name| vec![type_name.to_owned()])
+                .unwrap_or_default(),
+            Self::Unit(_) => vec![],
+        }
+    }
+
+    /// Applies per-field profile options to struct fields.
+    pub fn apply_field_options(&mut self, name: &str, options: &ProfileOptions) -> Result<()> {
+        if let Self::Struct(inner) = self {
+            for field in inner.fields.iter_mut() {
+                // Profile entries take precedence over fixed values from the specification
+                if let Some(fixed) = options
+                    .fixed_field_types
+                    .find_fixed_field(name, &field.name)
+                {
+                    for offset_name in fixed.version_offsets.iter() {
+                        if inner
+                            .version_offsets
+                            .iter()
+                            .any(|offset| &offset.name == offset_name)
+                        {
+                            anyhow::bail!(
+                                "Version offset {} used more than once in {}",
+                                offset_name,
+                                name
+                            );
+                        }
+
+                        match options.version_offsets.find_version_offset(offset_name) {
+                            Some(offset) => inner.version_offsets.push(offset),
+                            None => anyhow::bail!("Version offset not found: {}", offset_name),
+                        }
+                    }
+
+                    field.fixed = Some(fixed);
+                }
+                field.arc_wrap = options
+                    .arc_wrapped_types
+                    .in_field_wrapped(name, &field.name);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn render_stdout(&self, name: &str, json_schema: bool) {
         match self {
-            Self::Struct(value) => value.render_stdout(name),
-            Self::Enum(value) => value.render_stdout(name),
-            Self::Wrapper(value) => value.render_stdout(name),
-            Self::Unit(value) => value.render_stdout(name),
+            Self::Struct(value) => value.render_stdout(name, json_schema),
+            Self::Enum(value) => value.render_stdout(name, json_schema),
+            Self::Wrapper(value) => value.render_stdout(name, json_schema),
+            Self::Unit(value) => value.render_stdout(name, json_schema),
         }
     }
 }
 
 impl RustStruct {
-    pub fn render_stdout(&self, name: &str) {
+    pub fn render_stdout(&self, name: &str, json_schema: bool) {
         let mut fields = self.fields.clone();
-        if fields.iter().any(|field| {
-            field
-                .fixed
-                .as_ref()
-                .is_some_and(|fixed| fixed.is_query_version)
-        }) {
+        for offset in self.version_offsets.iter() {
             fields.push(RustField {
-                description: Some(
-                    "If set to `true`, \
-                    uses a query-only transaction version that's invalid for execution"
-                        .into(),
-                ),
-                name: "is_query".into(),
+                description: Some(offset.description.clone()),
+                name: offset.flag.clone(),
                 optional: false,
                 fixed: None,
                 arc_wrap: false,
+                auto_boxed: false,
                 type_name: "bool".into(),
                 serde_rename: None,
                 serde_flatten: false,
@@ -340,15 +1005,17 @@ impl RustStruct {
         let derive_serde = !self.need_custom_serde();
 
         if derive_serde
-            && self
-                .fields
-                .iter()
-                .any(|item| matches!(item.serializer, Some(SerializerOverride::SerdeAs(_))))
+            && self.fields.iter().any(|item| {
+                item.auto_boxed || matches!(item.serializer, Some(SerializerOverride::SerdeAs(_)))
+            })
         {
             println!("#[serde_as]");
         }
         if derive_serde {
             print_rust_derives(&self.with_serde_derives());
+            if json_schema {
+                print_json_schema_derive();
+            }
 
             if !self.allow_unknown_fields {
                 println!(
@@ -365,7 +1032,27 @@ impl RustStruct {
                 print_doc(doc, 4);
             }
 
-            for line in field.def_lines(4, derive_serde, false, false, false) {
+            let mut lines = field.def_lines(4, derive_serde, false, false, false);
+            if let (true, Some(schema_type)) =
+                (json_schema && derive_serde, field.json_schema_type())
+            {
+                let args = format!("feature = \"schemars\", schemars(with = \"{schema_type}\")");
+                if args.len() <= ATTR_FN_LIKE_WIDTH {
+                    lines.insert(lines.len() - 1, format!("    #[cfg_attr({args})]"));
+                } else {
+                    lines.splice(
+                        lines.len() - 1..lines.len() - 1,
+                        [
+                            String::from("    #[cfg_attr("),
+                            String::from("        feature = \"schemars\","),
+                            format!("        schemars(with = \"{schema_type}\")"),
+                            String::from("    )]"),
+                        ],
+                    );
+                }
+            }
+
+            for line in lines {
                 println!("{line}")
             }
         }
@@ -376,55 +1063,133 @@ impl RustStruct {
             println!();
 
             print_doc(&format!("Reference version of [{name}]."), 0);
-            println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+            if derive_serde {
+                if self
+                    .fields
+                    .iter()
+                    .any(|item| matches!(item.serializer, Some(SerializerOverride::SerdeAs(_))))
+                {
+                    println!("#[serde_as]");
+                }
+                println!("#[derive(Debug, Clone, PartialEq, Eq, Serialize)]");
+            } else {
+                println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+            }
             println!("pub struct {name}Ref<'a> {{");
 
-            for field in fields.iter().filter(|field| field.fixed.is_none()) {
-                for line in field.def_lines(4, false, true, false, false) {
+            for field in self.fields.iter().filter(|field| field.fixed.is_none()) {
+                for line in field.def_lines(4, derive_serde, true, false, false) {
                     println!("{line}")
                 }
             }
+            // Flags are cheaper to copy than to borrow
+            for offset in self.version_offsets.iter() {
+                println!("    pub {}: bool,", offset.flag);
+            }
 
             println!("}}");
         }
-    }
-
-    pub fn render_serde_stdout(&self, name: &str) {
-        self.render_impl_serialize_stdout(name);
-        println!();
-        self.render_impl_deserialize_stdout(name);
-    }
 
-    pub fn need_custom_serde(&self) -> bool {
-        self.serde_as_obj || self.fields.iter().any(|field| field.fixed.is_some())
-    }
+        if let Some(borrowed_fields) = &self.borrowed_fields {
+            println!();
 
-    fn render_impl_serialize_stdout(&self, name: &str) {
-        if self.serde_as_obj {
-            self.render_impl_obj_serialize_stdout(name);
-        } else {
-            self.render_impl_tagged_serialize_stdout(name);
-        }
+            print_doc(
+                &format!(
+                    "Borrowed version of [{name}], deserialized without copying strings out of \
+                    the input."
+                ),
+                0,
+            );
+            if borrowed_fields
+                .iter()
+                .any(|item| matches!(item.field.serializer, Some(SerializerOverride::SerdeAs(_))))
+            {
+                println!("#[serde_as]");
+            }
+            println!("#[derive(Debug, Clone, Deserialize)]");
+            if !self.allow_unknown_fields {
+                println!(
+                    "#[cfg_attr(feature = \"no_unknown_fields\", serde(deny_unknown_fields))]"
+                );
+            }
+            println!("pub struct {name}Borrowed<'a> {{");
+
+            for item in borrowed_fields.iter() {
+                let mut lines = item.field.def_lines(4, true, false, true, false);
+                // The type is only ever deserialized
+                lines.retain(|line| !line.contains("skip_serializing_if"));
+                if item.borrow {
+                    lines.insert(lines.len() - 1, String::from("    #[serde(borrow)]"));
+                }
+
+                for line in lines {
+                    println!("{line}")
+                }
+            }
+
+            println!("}}");
+        }
+
+        if let Some(builder_defaults) = &self.builder_defaults {
+            println!();
+            self.render_builder_stdout(name, builder_defaults);
+        }
+    }
+
+    pub fn render_serde_stdout(&self, name: &str) {
+        self.render_impl_serialize_stdout(name);
+        println!();
+        self.render_impl_deserialize_stdout(name);
+    }
+
+    pub fn need_custom_serde(&self) -> bool {
+        self.param_structure.is_some()
+            || self.fields.iter().any(|field| field.fixed.is_some())
+            || !self.excluded_fields.is_empty()
+    }
+
+    fn render_impl_serialize_stdout(&self, name: &str) {
+        match self.param_structure {
+            Some(param_structure) => {
+                self.render_impl_params_serialize_stdout(name, param_structure)
+            }
+            None => {
+                self.render_impl_tagged_serialize_stdout(name, false);
+
+                if self.extra_ref_type {
+                    println!();
+                    self.render_impl_tagged_serialize_stdout(name, true);
+                }
+            }
+        }
     }
 
     fn render_impl_deserialize_stdout(&self, name: &str) {
-        if self.serde_as_obj {
-            self.render_impl_both_deserialize_stdout(name);
-        } else {
-            self.render_impl_tagged_deserialize_stdout(name);
+        match self.param_structure {
+            Some(param_structure) => {
+                self.render_impl_params_deserialize_stdout(name, param_structure)
+            }
+            None => self.render_impl_tagged_deserialize_stdout(name),
         }
     }
 
-    fn render_impl_obj_serialize_stdout(&self, name: &str) {
-        self.render_impl_array_serialize_stdout_inner(name, false);
+    fn render_impl_params_serialize_stdout(&self, name: &str, param_structure: ParamStructure) {
+        let by_position = param_structure == ParamStructure::ByPosition;
+
+        self.render_impl_params_serialize_stdout_inner(name, false, by_position);
 
         if self.extra_ref_type {
             println!();
-            self.render_impl_array_serialize_stdout_inner(name, true);
+            self.render_impl_params_serialize_stdout_inner(name, true, by_position);
         }
     }
 
-    fn render_impl_array_serialize_stdout_inner(&self, name: &str, is_ref_type: bool) {
+    fn render_impl_params_serialize_stdout_inner(
+        &self,
+        name: &str,
+        is_ref_type: bool,
+        by_position: bool,
+    ) {
         println!(
             "impl Serialize for {}{} {{",
             name,
@@ -434,7 +1199,22 @@ impl RustStruct {
             "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
         );
 
+        if by_position {
+            println!("        use serde::ser::SerializeSeq;");
+            println!();
+            self.render_field_wrappers_stdout(true);
+            self.render_positional_serialization_stdout(is_ref_type);
+        } else {
+            self.render_named_serialization_stdout(is_ref_type);
+        }
+
+        println!("    }}");
+        println!("}}");
+    }
+
+    fn render_named_serialization_stdout(&self, is_ref_type: bool) {
         println!("        #[derive(Serialize)]");
+
         println!("        struct AsObject<'a> {{");
 
         for (ind_field, field) in self.fields.iter().enumerate() {
@@ -452,20 +1232,7 @@ impl RustStruct {
         println!("        }}");
         println!();
 
-        for (ind_field, field) in self.fields.iter().enumerate() {
-            if field.serializer.is_some() {
-                println!("        #[serde_as]");
-            }
-
-            println!("        #[derive(Serialize)]");
-            println!("        #[serde(transparent)]");
-            println!("        struct Field{ind_field}<'a> {{");
-            for line in field.def_lines(12, true, true, false, true).iter() {
-                println!("{line}");
-            }
-            println!("        }}");
-            println!();
-        }
+        self.render_field_wrappers_stdout(true);
 
         println!("        AsObject::serialize(");
         println!("            &AsObject {{");
@@ -505,13 +1272,101 @@ impl RustStruct {
         println!("            }},");
         println!("            serializer,");
         println!("        )");
+    }
 
-        println!("    }}");
-        println!("}}");
+    fn render_positional_serialization_stdout(&self, is_ref_type: bool) {
+        println!("        let mut seq = serializer.serialize_seq(None)?;");
+
+        for (ind_field, field) in self.fields.iter().enumerate() {
+            if field.optional {
+                // Optional params are left out unless followed by other params, which keeps them
+                // in place with `null`
+                let later_fields = &self.fields[ind_field..];
+                if later_fields.iter().all(|field| field.optional) {
+                    let conditions = later_fields
+                        .iter()
+                        .map(|field| format!("self.{}.is_some()", field.name))
+                        .collect::<Vec<_>>();
+
+                    let condition = format!("        if {} {{", conditions.join(" || "));
+                    if condition.len() <= MAX_LINE_LENGTH {
+                        println!("{condition}");
+                    } else {
+                        println!("        if {}", conditions[0]);
+                        for condition in conditions.iter().skip(1) {
+                            println!("            || {condition}");
+                        }
+                        println!("        {{");
+                    }
+                } else {
+                    println!("        {{");
+                }
+
+                let element = format!(
+                    "&self.{}.as_ref().map(|f| Field{} {{ value: f }})",
+                    field.name, ind_field
+                );
+                let line = format!("            seq.serialize_element({element})?;");
+                if line.len() <= MAX_LINE_LENGTH {
+                    println!("{line}");
+                } else {
+                    println!("            seq.serialize_element(");
+                    println!("                {element},");
+                    println!("            )?;");
+                }
+                println!("        }}");
+            } else if field.name.len() + if is_ref_type { 0 } else { 1 } > 6 {
+                println!("        seq.serialize_element(&Field{ind_field} {{");
+                println!(
+                    "            value: {}self.{},",
+                    if is_ref_type { "" } else { "&" },
+                    field.name
+                );
+                println!("        }})?;");
+            } else {
+                println!(
+                    "        seq.serialize_element(&Field{} {{ value: {}self.{} }})?;",
+                    ind_field,
+                    if is_ref_type { "" } else { "&" },
+                    field.name
+                );
+            }
+        }
+
+        println!("        seq.end()");
     }
 
-    fn render_impl_tagged_serialize_stdout(&self, name: &str) {
-        println!("impl Serialize for {name} {{");
+    /// Prints the transparent wrappers applying the serializers of fields, named after the
+    /// field indices.
+    fn render_field_wrappers_stdout(&self, is_ser: bool) {
+        for (ind_field, field) in self.fields.iter().enumerate() {
+            if field.serializer.is_some() {
+                println!("        #[serde_as]");
+            }
+
+            if is_ser {
+                println!("        #[derive(Serialize)]");
+                println!("        #[serde(transparent)]");
+                println!("        struct Field{ind_field}<'a> {{");
+            } else {
+                println!("        #[derive(Deserialize)]");
+                println!("        #[serde(transparent)]");
+                println!("        struct Field{ind_field} {{");
+            }
+            for line in field.def_lines(12, true, is_ser, false, true).iter() {
+                println!("{line}");
+            }
+            println!("        }}");
+            println!();
+        }
+    }
+
+    fn render_impl_tagged_serialize_stdout(&self, name: &str, is_ref_type: bool) {
+        println!(
+            "impl Serialize for {}{} {{",
+            name,
+            if is_ref_type { "Ref<'_>" } else { "" },
+        );
         println!(
             "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
         );
@@ -537,17 +1392,23 @@ impl RustStruct {
         println!();
 
         for field in self.fields.iter().filter_map(|field| field.fixed.as_ref()) {
-            if field.is_query_version {
-                println!(
-                    "        let {} = &(if self.is_query {{",
-                    escape_name(&field.name)
-                );
-                println!(
-                    "            {} + QUERY_VERSION_OFFSET",
-                    field.value.trim_start_matches('&')
-                );
+            if !field.version_offsets.is_empty() {
+                let value = field.value.trim_start_matches('&');
+
+                for (ind, offset) in self.offsets_of(field).enumerate() {
+                    if ind == 0 {
+                        println!(
+                            "        let {} = &(if self.{} {{",
+                            escape_name(&field.name),
+                            offset.flag
+                        );
+                    } else {
+                        println!("        }} else if self.{} {{", offset.flag);
+                    }
+                    println!("            {} + {}", value, offset.name);
+                }
                 println!("        }} else {{");
-                println!("            {}", field.value.trim_start_matches('&'));
+                println!("            {value}");
                 println!("        }});");
             } else {
                 println!(
@@ -567,9 +1428,18 @@ impl RustStruct {
                 Some(_) => {
                     println!("            {},", escape_name(&field.name))
                 }
+                // Optional arrays are borrowed as `Option<&[T]>` in `Tagged`
+                None if !is_ref_type && field.optional && field.type_name.starts_with("Vec<") => {
+                    println!(
+                        "            {}: self.{}.as_deref(),",
+                        escape_name(&field.name),
+                        escape_name(&field.name)
+                    )
+                }
                 None => println!(
-                    "            {}: &self.{},",
+                    "            {}: {}self.{},",
                     escape_name(&field.name),
+                    if is_ref_type { "" } else { "&" },
                     escape_name(&field.name)
                 ),
             }
@@ -583,114 +1453,12 @@ impl RustStruct {
         println!("}}");
     }
 
-    fn render_impl_both_deserialize_stdout(&self, name: &str) {
+    fn render_impl_params_deserialize_stdout(&self, name: &str, param_structure: ParamStructure) {
         println!("impl<'de> Deserialize<'de> for {name} {{");
         println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
 
-        println!("        #[derive(Deserialize)]");
-        println!("        struct AsObject {{");
-
-        for (ind_field, field) in self.fields.iter().enumerate() {
-            if field.optional {
-                println!("            #[serde(skip_serializing_if = \"Option::is_none\")]");
-                println!("            {}: Option<Field{}>,", field.name, ind_field);
-            } else {
-                println!("            {}: Field{},", field.name, ind_field);
-            }
-        }
-
-        println!("        }}");
-        println!();
-
-        for (ind_field, field) in self.fields.iter().enumerate() {
-            if field.serializer.is_some() {
-                println!("        #[serde_as]");
-            }
-
-            println!("        #[derive(Deserialize)]");
-            println!("        #[serde(transparent)]");
-            println!("        struct Field{ind_field} {{");
-            for line in field.def_lines(12, true, false, false, true).iter() {
-                println!("{line}");
-            }
-            println!("        }}");
-            println!();
-        }
-
-        println!("        let temp = serde_json::Value::deserialize(deserializer)?;");
-        println!();
-        println!(
-            "        if let Ok(mut elements) = Vec::<serde_json::Value>::deserialize(&temp) {{"
-        );
-
-        if self.fields.iter().any(|field| field.optional) {
-            println!("            let element_count = elements.len();");
-            println!();
-        }
-
-        for (ind_field, field) in self.fields.iter().enumerate().rev() {
-            if field.optional {
-                println!("            let field{ind_field} = if element_count > {ind_field} {{");
-                println!("                Some(");
-                println!("                    serde_json::from_value::<Field{ind_field}>(elements.pop().unwrap()).map_err(|err| {{");
-                println!("                        serde::de::Error::custom(format!(\"failed to parse element: {{err}}\"))");
-                println!("                    }})?,");
-                println!("                )");
-                println!("            }} else {{");
-                println!("                None");
-                println!("            }};");
-            } else {
-                println!(
-                    "            let field{ind_field} = serde_json::from_value::<Field{ind_field}>("
-                );
-                println!("                elements");
-                println!("                    .pop()");
-                println!("                    .ok_or_else(|| serde::de::Error::custom(\"invalid sequence length\"))?,");
-                println!("            )");
-                println!("            .map_err(|err| serde::de::Error::custom(format!(\"failed to parse element: {{err}}\")))?;");
-            }
-        }
-
-        println!();
-
-        println!("            Ok(Self {{");
-
-        for (ind_field, field) in self.fields.iter().enumerate() {
-            if field.optional {
-                println!(
-                    "                {}: field{}.map(|f| f.value),",
-                    field.name, ind_field
-                );
-            } else {
-                println!("                {}: field{}.value,", field.name, ind_field);
-            }
-        }
-
-        println!("            }})");
-
-        println!("        }} else if let Ok(object) = AsObject::deserialize(&temp) {{");
-
-        println!("            Ok(Self {{");
-
-        for field in self.fields.iter() {
-            if field.optional {
-                println!(
-                    "                {}: object.{}.map(|f| f.value),",
-                    field.name, field.name
-                );
-            } else {
-                println!(
-                    "                {}: object.{}.value,",
-                    field.name, field.name
-                );
-            }
-        }
-
-        println!("            }})");
-
-        println!("        }} else {{");
-        println!("            Err(serde::de::Error::custom(\"invalid sequence length\"))");
-        println!("        }}");
+        self.render_field_wrappers_stdout(false);
+        render_params_visitor_stdout(name, param_structure, &self.fields);
 
         println!("    }}");
         println!("}}");
@@ -700,11 +1468,12 @@ impl RustStruct {
         println!("impl<'de> Deserialize<'de> for {name} {{");
         println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
 
-        if self
-            .fields
-            .iter()
-            .any(|item| matches!(item.serializer, Some(SerializerOverride::SerdeAs(_))))
-        {
+        // Fixed fields are deserialized as `Option`, which turns `#[serde(with)]` into `serde_as`
+        if self.fields.iter().any(|item| match item.serializer {
+            Some(SerializerOverride::SerdeAs(_)) => true,
+            Some(SerializerOverride::Serde(_)) => item.fixed.is_some(),
+            None => false,
+        }) {
             println!("        #[serde_as]");
         }
 
@@ -725,6 +1494,7 @@ impl RustStruct {
                     optional: false,
                     fixed: Some(fixed.to_owned()),
                     arc_wrap: false,
+                    auto_boxed: false,
                     type_name: if fixed.must_present_in_deser {
                         field.type_name.to_owned()
                     } else {
@@ -743,25 +1513,107 @@ impl RustStruct {
             }
         }
 
+        for excluded_field in self.excluded_fields.iter() {
+            let field_name = to_rust_field_name(excluded_field);
+            if &field_name != excluded_field {
+                println!("            #[serde(rename = \"{excluded_field}\")]");
+            }
+            println!(
+                "            pub {}: Option<serde::de::IgnoredAny>,",
+                escape_name(&field_name)
+            );
+        }
+
         println!("        }}");
         println!();
         println!("        let tagged = Tagged::deserialize(deserializer)?;");
         println!();
 
+        if !self.excluded_fields.is_empty() {
+            let conditions = self
+                .excluded_fields
+                .iter()
+                .map(|field| {
+                    format!(
+                        "tagged.{}.is_some()",
+                        escape_name(&to_rust_field_name(field))
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let condition_line = format!("        if {}", conditions.join(" && "));
+            if condition_line.len() + 2 <= MAX_LINE_LENGTH {
+                println!("{condition_line} {{");
+            } else if condition_line.len() <= MAX_LINE_LENGTH {
+                println!("{condition_line}");
+                println!("        {{");
+            } else {
+                println!("        if {}", conditions[0]);
+                for condition in conditions.iter().skip(1) {
+                    println!("            && {condition}");
+                }
+                println!("        {{");
+            }
+
+            println!("            return Err(serde::de::Error::custom(");
+            println!(
+                "                \"{} must not all be present\",",
+                self.excluded_fields
+                    .iter()
+                    .map(|field| format!("`{field}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            println!("            ));");
+            println!("        }}");
+            println!();
+        }
+
         for fixed_field in self.fields.iter().filter_map(|field| field.fixed.as_ref()) {
-            if fixed_field.is_query_version {
-                println!(
-                    "        let is_query = if tagged.{} == {} {{",
-                    fixed_field.name,
-                    fixed_field.value.trim_start_matches('&')
-                );
-                println!("            false");
+            if !fixed_field.version_offsets.is_empty() {
+                let value = fixed_field.value.trim_start_matches('&');
+                let offsets = self.offsets_of(fixed_field).collect::<Vec<_>>();
+
+                // Flags are mutually exclusive as at most one offset is applied
+                let flag_values = |applied: Option<usize>| {
+                    let values = (0..offsets.len())
+                        .map(|ind| {
+                            if applied == Some(ind) {
+                                "true"
+                            } else {
+                                "false"
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    if values.len() == 1 {
+                        values[0].to_owned()
+                    } else {
+                        format!("({})", values.join(", "))
+                    }
+                };
+
+                let flags = offsets
+                    .iter()
+                    .map(|offset| offset.flag.as_str())
+                    .collect::<Vec<_>>();
                 println!(
-                    "        }} else if tagged.{} == {} + QUERY_VERSION_OFFSET {{",
+                    "        let {} = if tagged.{} == {} {{",
+                    if flags.len() == 1 {
+                        flags[0].to_owned()
+                    } else {
+                        format!("({})", flags.join(", "))
+                    },
                     fixed_field.name,
-                    fixed_field.value.trim_start_matches('&')
+                    value
                 );
-                println!("            true");
+                println!("            {}", flag_values(None));
+                for (ind, offset) in offsets.iter().enumerate() {
+                    println!(
+                        "        }} else if tagged.{} == {} + {} {{",
+                        fixed_field.name, value, offset.name
+                    );
+                    println!("            {}", flag_values(Some(ind)));
+                }
                 println!("        }} else {{");
                 println!(
                     "            return Err(serde::de::Error::custom(\"invalid `{}` value\"));",
@@ -810,7 +1662,9 @@ impl RustStruct {
             println!(
                 "            {}: {},",
                 escape_name(&field.name),
-                if field.arc_wrap {
+                if field.auto_boxed && field.optional {
+                    format!("tagged.{}.map(OwnedPtr::new)", escape_name(&field.name))
+                } else if field.arc_wrap || field.auto_boxed {
                     format!("OwnedPtr::new(tagged.{})", escape_name(&field.name))
                 } else {
                     format!("tagged.{}", escape_name(&field.name))
@@ -818,13 +1672,8 @@ impl RustStruct {
             );
         }
 
-        if self.fields.iter().any(|field| {
-            field
-                .fixed
-                .as_ref()
-                .is_some_and(|fixed| fixed.is_query_version)
-        }) {
-            println!("            is_query,",);
+        for offset in self.version_offsets.iter() {
+            println!("            {},", offset.flag);
         }
 
         println!("        }})");
@@ -833,26 +1682,594 @@ impl RustStruct {
         println!("}}");
     }
 
-    fn with_default_derives(&self) -> IndexSet<String> {
-        let mut derives: IndexSet<_> = self.derives.iter().cloned().collect();
-        derives.insert("Debug".into());
-        derives.insert("Clone".into());
-        derives.insert("PartialEq".into());
-        derives.insert("Eq".into());
-        derives
-    }
+    fn render_arbitrary_fn_stdout(&self) {
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| field.fixed.is_none())
+            .collect::<Vec<_>>();
+        print_arbitrary_fn_signature(!fields.is_empty() || !self.version_offsets.is_empty());
 
-    fn with_serde_derives(&self) -> IndexSet<String> {
-        let mut derives = self.with_default_derives();
-        derives.insert("Serialize".into());
-        derives.insert("Deserialize".into());
-        derives
-    }
-}
+        // Offsets of the same fixed field are mutually exclusive, so at most one gets picked
+        let offset_fields = self
+            .fields
+            .iter()
+            .filter_map(|field| field.fixed.as_ref().map(|fixed| (&field.name, fixed)))
+            .filter(|(_, fixed)| fixed.version_offsets.len() > 1)
+            .collect::<Vec<_>>();
+        for (field_name, fixed) in offset_fields.iter() {
+            println!(
+                "        let {}_offset = u.choose_index({})?;",
+                field_name,
+                fixed.version_offsets.len() + 1
+            );
+        }
+        if !offset_fields.is_empty() {
+            println!();
+        }
 
-impl RustEnum {
-    pub fn render_stdout(&self, name: &str) {
-        print_rust_derives(&self.with_default_derives());
+        println!("        Ok(Self {{");
+
+        for field in fields.iter() {
+            print_struct_field_init(escape_name(&field.name), &field.arbitrary_expr());
+        }
+        for offset in self.version_offsets.iter() {
+            let picked = offset_fields.iter().find_map(|(field_name, fixed)| {
+                fixed
+                    .version_offsets
+                    .iter()
+                    .position(|name| name == &offset.name)
+                    .map(|ind| format!("{}_offset == {}", field_name, ind + 1))
+            });
+
+            print_struct_field_init(&offset.flag, picked.as_deref().unwrap_or("u.arbitrary()?"));
+        }
+
+        println!("        }})");
+        println!("    }}");
+    }
+
+    /// Renders a `json_schema` fn describing the JSON of the custom serde impls, with fixed fields
+    /// accepting their fixed values only.
+    fn render_json_schema_fn_stdout(&self, samples: &SampleSynthesizer) {
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| field.fixed.is_none())
+            .collect::<Vec<_>>();
+        print_json_schema_fn_signature(!fields.is_empty());
+
+        for field in fields.iter() {
+            let schema_type = field.json_schema_type().unwrap_or_else(|| {
+                if field.optional {
+                    format!("Option<{}>", field.type_name)
+                } else {
+                    field.type_name.clone()
+                }
+            });
+
+            let line = format!(
+                "        let {} = generator.subschema_for::<{}>();",
+                escape_name(&field.name),
+                schema_type
+            );
+            if line.len() <= MAX_LINE_LENGTH {
+                println!("{line}");
+            } else {
+                println!("        let {} =", escape_name(&field.name));
+                println!("            generator.subschema_for::<{schema_type}>();");
+            }
+        }
+
+        let lines = match self.param_structure {
+            Some(ParamStructure::ByName) => self.params_object_schema_lines(),
+            Some(ParamStructure::ByPosition) => self.params_array_schema_lines(),
+            Some(ParamStructure::Either) => {
+                let mut lines = vec![String::from("{"), String::from("    \"anyOf\": [")];
+                for variant_lines in [
+                    self.params_object_schema_lines(),
+                    self.params_array_schema_lines(),
+                ] {
+                    let variant_len = variant_lines.len();
+                    lines.extend(variant_lines.into_iter().enumerate().map(|(ind, line)| {
+                        if ind == variant_len - 1 {
+                            format!("        {line},")
+                        } else {
+                            format!("        {line}")
+                        }
+                    }));
+                }
+                lines.push(String::from("    ],"));
+                lines.push(String::from("}"));
+                lines
+            }
+            None => {
+                self.render_fixed_field_values_stdout();
+                self.tagged_schema_lines(samples)
+            }
+        };
+
+        if !fields.is_empty() {
+            println!();
+        }
+        print_json_schema_macro_stdout(&lines);
+        println!("    }}");
+    }
+
+    /// Prints the serialized values of fixed fields with offsets, which are only known at runtime.
+    fn render_fixed_field_values_stdout(&self) {
+        for field in self.fields.iter() {
+            let Some(fixed) = &field.fixed else {
+                continue;
+            };
+            if fixed.version_offsets.is_empty() {
+                continue;
+            }
+
+            let value = fixed.value.trim_start_matches('&');
+            let values = std::iter::once(value.to_owned())
+                .chain(
+                    self.offsets_of(fixed)
+                        .map(|offset| format!("{} + {}", value, offset.name)),
+                )
+                .collect::<Vec<_>>();
+
+            // Same as `UfeHex`
+            let expr = format!(
+                "[{}].map(|value| format!(\"{{value:#x}}\"))",
+                values.join(", ")
+            );
+            let line = format!("        let {} = {expr};", escape_name(&field.name));
+            if line.len() <= MAX_LINE_LENGTH {
+                println!("{line}");
+            } else if expr.len() + 13 <= MAX_LINE_LENGTH {
+                println!("        let {} =", escape_name(&field.name));
+                println!("            {expr};");
+            } else {
+                println!(
+                    "        let {} = [{}]",
+                    escape_name(&field.name),
+                    values.join(", ")
+                );
+                println!("            .map(|value| format!(\"{{value:#x}}\"));");
+            }
+        }
+    }
+
+    /// Gets the schema of tagged structs as JSON lines for `schemars::json_schema!`, referencing
+    /// the field schemas bound by field names.
+    fn tagged_schema_lines(&self, samples: &SampleSynthesizer) -> Vec<String> {
+        let mut properties = vec![];
+        let mut required = vec![];
+        let mut flattened = vec![];
+
+        for field in self.fields.iter() {
+            if field.serde_flatten {
+                flattened.push(escape_name(&field.name));
+                continue;
+            }
+
+            let key = field.serde_rename.as_ref().unwrap_or(&field.name);
+            let value = match &field.fixed {
+                Some(fixed) if !fixed.version_offsets.is_empty() => {
+                    format!("{{ \"enum\": {} }}", escape_name(&field.name))
+                }
+                Some(fixed) => {
+                    let serializer = field.serializer.as_ref().map(|value| match value {
+                        SerializerOverride::Serde(adapter) => adapter.module,
+                        SerializerOverride::SerdeAs(serde_as) => serde_as.as_str(),
+                    });
+                    let value = samples
+                        .for_fixed_value(&fixed.value, &field.type_name, serializer)
+                        .unwrap_or_else(|| panic!("unsupported fixed value {}", fixed.value));
+                    format!("{{ \"const\": {value} }}")
+                }
+                None => escape_name(&field.name).to_owned(),
+            };
+
+            properties.push(format!("        \"{key}\": {value},"));
+            if !field.optional {
+                required.push(format!("\"{key}\""));
+            }
+        }
+
+        let mut lines = vec![
+            String::from("{"),
+            String::from("    \"type\": \"object\","),
+            String::from("    \"properties\": {"),
+        ];
+        lines.extend(properties);
+        lines.push(String::from("    },"));
+        push_json_array_lines(&mut lines, "required", &required);
+        if !self.excluded_fields.is_empty() {
+            let excluded = self
+                .excluded_fields
+                .iter()
+                .map(|name| format!("\"{name}\""))
+                .collect::<Vec<_>>();
+            lines.push(String::from("    \"not\": {"));
+            push_json_array_lines(&mut lines, "required", &excluded);
+            lines.push(String::from("    },"));
+        }
+        if !flattened.is_empty() {
+            push_json_array_lines(&mut lines, "allOf", &flattened);
+        }
+        lines.push(String::from("}"));
+
+        lines
+    }
+
+    /// Gets the schema of params by name as JSON lines, like [`Self::tagged_schema_lines`].
+    fn params_object_schema_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            String::from("{"),
+            String::from("    \"type\": \"object\","),
+            String::from("    \"properties\": {"),
+        ];
+        for field in self.fields.iter() {
+            lines.push(format!(
+                "        \"{}\": {},",
+                field.name,
+                escape_name(&field.name)
+            ));
+        }
+        lines.push(String::from("    },"));
+        push_json_array_lines(
+            &mut lines,
+            "required",
+            &self
+                .fields
+                .iter()
+                .filter(|field| !field.optional)
+                .map(|field| format!("\"{}\"", field.name))
+                .collect::<Vec<_>>(),
+        );
+        lines.push(String::from("}"));
+
+        lines
+    }
+
+    /// Gets the schema of params by position as JSON lines, like [`Self::tagged_schema_lines`].
+    /// Trailing optional params can be left out.
+    fn params_array_schema_lines(&self) -> Vec<String> {
+        let min_items = self
+            .fields
+            .iter()
+            .rposition(|field| !field.optional)
+            .map_or(0, |ind| ind + 1);
+
+        let mut lines = vec![String::from("{"), String::from("    \"type\": \"array\",")];
+        push_json_array_lines(
+            &mut lines,
+            "prefixItems",
+            &self
+                .fields
+                .iter()
+                .map(|field| escape_name(&field.name).to_owned())
+                .collect::<Vec<_>>(),
+        );
+        lines.push(format!("    \"minItems\": {min_items},"));
+        lines.push(format!("    \"maxItems\": {},", self.fields.len()));
+        lines.push(String::from("}"));
+
+        lines
+    }
+
+    /// Renders a builder taking required fields up front, and optional fields, defaulted fields
+    /// and offset flags through setters. Fixed fields are left out as they're not part of the
+    /// struct.
+    fn render_builder_stdout(&self, name: &str, defaults: &[BuilderDefault]) {
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| field.fixed.is_none())
+            .collect::<Vec<_>>();
+
+        print_doc(
+            &format!("Builder for [{name}], created with [{name}::builder]."),
+            0,
+        );
+        println!("#[derive(Debug, Clone)]");
+        println!("pub struct {name}Builder {{");
+        println!("    inner: {name},");
+        println!("}}");
+        println!();
+
+        let default_of = |field: &RustField| {
+            defaults
+                .iter()
+                .find(|default| default.field == field.name)
+                .map(|default| default.value.as_str())
+        };
+
+        let params = fields
+            .iter()
+            .filter(|field| !field.optional && default_of(field).is_none())
+            .map(|field| {
+                format!(
+                    "{}: {}",
+                    escape_name(&field.name),
+                    field.builder_param_type()
+                )
+            })
+            .collect::<Vec<_>>();
+
+        println!("impl {name} {{");
+        print_doc(
+            &if defaults.is_empty() {
+                format!("Starts building a [{name}] from its required fields.")
+            } else {
+                format!(
+                    "Starts building a [{name}] from its required fields, other than those starting \
+                    out with the defaults documented on their setters."
+                )
+            },
+            4,
+        );
+        // Clippy counts arguments above 7 as too many
+        if params.len() > 7 {
+            println!("    #[allow(clippy::too_many_arguments)]");
+        }
+        let signature_line = format!(
+            "    pub fn builder({}) -> {name}Builder {{",
+            params.join(", ")
+        );
+        if signature_line.len() <= MAX_LINE_LENGTH {
+            println!("{signature_line}");
+        } else {
+            println!("    pub fn builder(");
+            for param in params.iter() {
+                println!("        {param},");
+            }
+            println!("    ) -> {name}Builder {{");
+        }
+        println!("        {name}Builder {{");
+        println!("            inner: Self {{");
+        for field in fields.iter() {
+            let field_name = escape_name(&field.name);
+            if field.optional {
+                println!("                {field_name}: None,");
+            } else if let Some(value) = default_of(field) {
+                println!("                {field_name}: {value},");
+            } else if field.arc_wrap || field.auto_boxed {
+                println!("                {field_name}: {field_name}.into(),");
+            } else {
+                println!("                {field_name},");
+            }
+        }
+        for offset in self.version_offsets.iter() {
+            println!("                {}: false,", offset.flag);
+        }
+        println!("            }},");
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+        println!();
+
+        println!("impl {name}Builder {{");
+        for field in fields
+            .iter()
+            .filter(|field| field.optional || default_of(field).is_some())
+        {
+            let field_name = escape_name(&field.name);
+
+            if let Some(doc) = &field.description {
+                print_doc(doc, 4);
+            }
+            if let Some(value) = default_of(field) {
+                if field.description.is_some() {
+                    println!("    ///");
+                }
+                print_doc(&format!("Defaults to `{value}`."), 4);
+            }
+            let param = format!("{field_name}: {}", field.builder_param_type());
+            let signature_line = format!("    pub fn {field_name}(mut self, {param}) -> Self {{");
+            if signature_line.len() <= MAX_LINE_LENGTH {
+                println!("{signature_line}");
+            } else {
+                println!("    pub fn {field_name}(");
+                println!("        mut self,");
+                println!("        {param},");
+                println!("    ) -> Self {{");
+            }
+            let value = if field.arc_wrap || field.auto_boxed {
+                format!("{field_name}.into()")
+            } else {
+                field_name.to_owned()
+            };
+            if field.optional {
+                println!("        self.inner.{field_name} = Some({value});");
+            } else {
+                println!("        self.inner.{field_name} = {value};");
+            }
+            println!("        self");
+            println!("    }}");
+            println!();
+        }
+        // Offsets of the same fixed field are mutually exclusive
+        let exclusive_fields = self
+            .fields
+            .iter()
+            .filter_map(|field| Some((field, field.fixed.as_ref()?)))
+            .filter(|(_, fixed)| self.offsets_of(fixed).count() > 1)
+            .collect::<Vec<_>>();
+
+        for offset in self.version_offsets.iter() {
+            let mut conflicting: IndexSet<&str> = IndexSet::new();
+            for (_, fixed) in exclusive_fields.iter() {
+                if fixed.version_offsets.contains(&offset.name) {
+                    conflicting.extend(
+                        self.offsets_of(fixed)
+                            .filter(|other| other.name != offset.name)
+                            .map(|other| other.flag.as_str()),
+                    );
+                }
+            }
+
+            print_doc(&offset.description, 4);
+            if !conflicting.is_empty() {
+                println!("    ///");
+                print_doc(
+                    &format!(
+                        "Setting it clears {}, as offsets of the same field can't be combined.",
+                        conflicting
+                            .iter()
+                            .map(|flag| format!("[Self::{flag}]"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    4,
+                );
+            }
+            println!(
+                "    pub fn {}(mut self, {}: bool) -> Self {{",
+                offset.flag, offset.flag
+            );
+            println!("        self.inner.{} = {};", offset.flag, offset.flag);
+            if !conflicting.is_empty() {
+                println!("        if {} {{", offset.flag);
+                for flag in conflicting.iter() {
+                    println!("            self.inner.{flag} = false;");
+                }
+                println!("        }}");
+            }
+            println!("        self");
+            println!("    }}");
+            println!();
+        }
+
+        print_doc(
+            &format!("Builds the [{name}], rejecting version offsets that can't be combined."),
+            4,
+        );
+        let output = format!("Result<{name}, {name}BuildError>");
+        if format!("    pub fn build(self) -> {output} {{").len() <= MAX_LINE_LENGTH {
+            println!("    pub fn build(self) -> {output} {{");
+        } else {
+            println!("    pub fn build(");
+            println!("        self,");
+            if format!("    ) -> {output} {{").len() <= MAX_LINE_LENGTH {
+                println!("    ) -> {output} {{");
+            } else {
+                println!("    ) -> Result<");
+                println!("        {name},");
+                println!("        {name}BuildError,");
+                println!("    > {{");
+            }
+        }
+        for (field, fixed) in exclusive_fields.iter() {
+            let flags = self
+                .offsets_of(fixed)
+                .map(|offset| format!("self.inner.{}", offset.flag))
+                .collect::<Vec<_>>();
+            let applied = flags.join(", ");
+            let applied_line = format!("        let applied = [{applied}];");
+            if applied_line.len() <= MAX_LINE_LENGTH && applied.len() <= ARRAY_WIDTH {
+                println!("{applied_line}");
+            } else {
+                println!("        let applied = [");
+                for flag in flags.iter() {
+                    println!("            {flag},");
+                }
+                println!("        ];");
+            }
+            println!("        if applied.iter().filter(|applied| **applied).count() > 1 {{");
+            let field_name = field.serde_rename.as_ref().unwrap_or(&field.name);
+            let return_line = format!(
+                "            return Err({name}BuildError::ConflictingOffsets {{ field: \"{field_name}\" }});"
+            );
+            let error_line = format!(
+                "                {name}BuildError::ConflictingOffsets {{ field: \"{field_name}\" }},"
+            );
+            if return_line.len() < MAX_LINE_LENGTH {
+                println!("{return_line}");
+            } else if error_line.len() <= MAX_LINE_LENGTH {
+                println!("            return Err(");
+                println!("{error_line}");
+                println!("            );");
+            } else {
+                println!("            return Err(");
+                println!("                {name}BuildError::ConflictingOffsets {{");
+                println!("                    field: \"{field_name}\",");
+                println!("                }},");
+                println!("            );");
+            }
+            println!("        }}");
+        }
+        println!("        Ok(self.inner)");
+        println!("    }}");
+        println!("}}");
+        println!();
+
+        print_doc(
+            &format!("Error building a [{name}] with [{name}Builder::build]."),
+            0,
+        );
+        println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+        println!("pub enum {name}BuildError {{");
+        println!(
+            "    /// Several version offsets were applied to a fixed field taking at most one"
+        );
+        println!("    ConflictingOffsets {{");
+        println!("        /// Name of the fixed field in the specification");
+        println!("        field: &'static str,");
+        println!("    }},");
+        println!("}}");
+        println!();
+        println!("#[cfg(feature = \"std\")]");
+        println!("impl std::error::Error for {name}BuildError {{}}");
+        println!();
+        println!("impl core::fmt::Display for {name}BuildError {{");
+        println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+        println!("        match self {{");
+        println!("            Self::ConflictingOffsets {{ field }} => {{");
+        println!(
+            "                write!(f, \"conflicting version offsets applied to `{{}}`\", field)"
+        );
+        println!("            }}");
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+    }
+
+    /// Gets the offsets that can be applied to a fixed field, in profile order.
+    fn offsets_of<'a>(
+        &'a self,
+        field: &'a FixedField,
+    ) -> impl Iterator<Item = &'a VersionOffset> + 'a {
+        field.version_offsets.iter().filter_map(|name| {
+            self.version_offsets
+                .iter()
+                .find(|offset| &offset.name == name)
+        })
+    }
+
+    fn with_default_derives(&self) -> IndexSet<String> {
+        let mut derives: IndexSet<_> = self.derives.iter().cloned().collect();
+        derives.insert("Debug".into());
+        derives.insert("Clone".into());
+        derives.insert("PartialEq".into());
+        derives.insert("Eq".into());
+        derives
+    }
+
+    fn with_serde_derives(&self) -> IndexSet<String> {
+        let mut derives = self.with_default_derives();
+        derives.insert("Serialize".into());
+        derives.insert("Deserialize".into());
+        derives
+    }
+}
+
+impl RustEnum {
+    pub fn render_stdout(&self, name: &str, json_schema: bool) {
+        print_rust_derives(&self.with_default_derives());
+        if json_schema && !self.need_custom_serde() {
+            print_json_schema_derive();
+        }
+        if self.open.as_ref().is_some_and(|open| open.non_exhaustive) {
+            println!("#[non_exhaustive]");
+        }
         println!("pub enum {name} {{");
 
         for variant in self.variants.iter() {
@@ -860,10 +2277,13 @@ impl RustEnum {
                 print_doc(doc, 4);
             }
 
-            if let Some(rename) = &variant.serde_name {
+            if let (Some(rename), false) = (&variant.serde_name, self.need_custom_serde()) {
                 println!("    #[serde(rename = \"{rename}\")]");
             }
             match &variant.wraps {
+                Some(inner) if variant.boxed => {
+                    println!("    {}(OwnedPtr<{}>),", variant.name, inner.type_name);
+                }
                 Some(inner) => {
                     println!("    {}({}),", variant.name, inner.type_name);
                 }
@@ -873,8 +2293,18 @@ impl RustEnum {
             }
         }
 
+        if self.open.is_some() {
+            print_doc("Value not defined in the specification", 4);
+            println!("    Unknown(String),");
+        }
+
         println!("}}");
 
+        if self.is_string_enum() {
+            println!();
+            self.render_impl_str_stdout(name);
+        }
+
         if self.is_error {
             println!();
             println!("#[cfg(feature = \"std\")]");
@@ -970,48 +2400,399 @@ impl RustEnum {
             println!("        }}");
             println!("    }}");
             println!("}}");
+
+            println!();
+            self.render_json_rpc_error_stdout(name);
         }
     }
 
-    pub fn need_custom_serde(&self) -> bool {
-        false
-    }
+    /// Renders the raw JSON-RPC error object along with conversions from and into the error enum.
+    fn render_json_rpc_error_stdout(&self, name: &str) {
+        println!("/// Raw JSON-RPC error object.");
+        println!("#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]");
+        println!("pub struct JsonRpcError {{");
+        println!("    pub code: i64,");
+        println!("    pub message: String,");
+        println!("    #[serde(default, skip_serializing_if = \"Option::is_none\")]");
+        println!("    pub data: Option<serde_json::Value>,");
+        println!("}}");
+        println!();
 
-    fn with_default_derives(&self) -> IndexSet<String> {
-        let mut derives: IndexSet<_> = self.derives.iter().cloned().collect();
-        derives.insert("Debug".into());
-        derives.insert("Clone".into());
+        let has_data = self.variants.iter().any(|variant| variant.wraps.is_some());
+        let all_data = self.variants.iter().all(|variant| variant.wraps.is_some());
 
-        // Implement `Copy` when no variant wraps other types
-        if !self.variants.iter().any(|variant| variant.wraps.is_some()) {
-            derives.insert("Copy".into());
+        // Errors with unknown codes or malformed data are handed back as is
+        println!("impl TryFrom<JsonRpcError> for {name} {{");
+        println!("    type Error = JsonRpcError;");
+        println!();
+        println!("    fn try_from(value: JsonRpcError) -> Result<Self, Self::Error> {{");
+        if has_data {
+            println!("        let data = value.data.as_ref();");
         }
+        println!("        let decoded = match value.code {{");
 
-        derives.insert("PartialEq".into());
-        derives.insert("Eq".into());
-        derives.insert("Serialize".into());
-        derives.insert("Deserialize".into());
-        derives
-    }
-}
-
-impl RustWrapper {
-    pub fn render_stdout(&self, name: &str) {
-        println!("#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]");
-        println!("pub struct {}(pub {});", name, self.type_name);
-    }
-
-    pub fn need_custom_serde(&self) -> bool {
-        false
-    }
-}
+        let mut seen_codes = HashSet::new();
+        for variant in self.variants.iter() {
+            let error_code = variant
+                .error_code
+                .expect("error code to be present for errors");
+            if !seen_codes.insert(error_code) {
+                continue;
+            }
 
-impl RustUnit {
-    pub fn render_stdout(&self, name: &str) {
-        if self.need_custom_serde() {
+            match &variant.wraps {
+                Some(wraps) => {
+                    println!("            {error_code} => data");
+                    println!(
+                        "                .and_then(|data| {}.ok())",
+                        wraps.data_decoding_expr("data")
+                    );
+                    if variant.boxed {
+                        println!("                .map(OwnedPtr::new)");
+                    }
+                    println!("                .map(Self::{}),", variant.name);
+                }
+                None => println!(
+                    "            {} => Some(Self::{}),",
+                    error_code, variant.name
+                ),
+            }
+        }
+
+        println!("            _ => None,");
+        println!("        }};");
+        println!();
+        println!("        decoded.ok_or(value)");
+        println!("    }}");
+        println!("}}");
+        println!();
+
+        println!("impl From<{name}> for JsonRpcError {{");
+        println!("    fn from(value: {name}) -> Self {{");
+        if has_data {
+            println!("        // Data types are always representable as JSON values");
+            println!("        let data = match &value {{");
+            for variant in self.variants.iter() {
+                if let Some(wraps) = &variant.wraps {
+                    let encoding_expr =
+                        wraps.data_encoding_expr(if variant.boxed { "&**data" } else { "data" });
+                    let variant_handler = format!(
+                        "            {}::{}(data) => {}.ok(),",
+                        name, variant.name, encoding_expr
+                    );
+
+                    if variant_handler.len() <= MAX_LINE_LENGTH {
+                        println!("{variant_handler}");
+                    } else {
+                        println!("            {}::{}(data) => {{", name, variant.name);
+                        println!("                {encoding_expr}.ok()");
+                        println!("            }}");
+                    }
+                }
+            }
+            if !all_data {
+                println!("            _ => None,");
+            }
+            println!("        }};");
+            println!();
+        }
+        println!("        Self {{");
+        println!("            code: value.code().into(),");
+        println!("            message: value.message().into(),");
+        if has_data {
+            println!("            data,");
+        } else {
+            println!("            data: None,");
+        }
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+    }
+
+    pub fn render_serde_stdout(&self, name: &str) {
+        self.render_impl_serialize_stdout(name);
+        println!();
+        self.render_impl_deserialize_stdout(name);
+    }
+
+    pub fn need_custom_serde(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Renders `Arbitrary::arbitrary`, never picking the `Unknown` variant of open enums as its
+    /// values aren't defined in the specification.
+    fn render_arbitrary_fn_stdout(&self) {
+        let variant_exprs = self
+            .variants
+            .iter()
+            .map(|variant| match &variant.wraps {
+                Some(wraps) if variant.boxed => format!(
+                    "Self::{}(OwnedPtr::new({}))",
+                    variant.name,
+                    wraps.arbitrary_expr()
+                ),
+                Some(wraps) => format!("Self::{}({})", variant.name, wraps.arbitrary_expr()),
+                None => format!("Self::{}", variant.name),
+            })
+            .collect::<Vec<_>>();
+
+        if let [variant_expr] = &variant_exprs[..] {
+            print_arbitrary_fn_signature(self.variants[0].wraps.is_some());
+            println!("        Ok({variant_expr})");
+        } else {
+            print_arbitrary_fn_signature(true);
+            println!(
+                "        Ok(match u.choose_index({})? {{",
+                variant_exprs.len()
+            );
+            for (ind, variant_expr) in variant_exprs.iter().enumerate() {
+                if ind == variant_exprs.len() - 1 {
+                    print_match_arm("_", variant_expr);
+                } else {
+                    print_match_arm(&ind.to_string(), variant_expr);
+                }
+            }
+            println!("        }})");
+        }
+
+        println!("    }}");
+    }
+
+    /// Renders a `json_schema` fn for open enums, which only lists the values from the
+    /// specification although unknown ones are deserialized too.
+    fn render_json_schema_fn_stdout(&self) {
+        print_json_schema_fn_signature(false);
+
+        let mut lines = vec![String::from("{"), String::from("    \"type\": \"string\",")];
+        push_json_array_lines(
+            &mut lines,
+            "enum",
+            &self
+                .variants
+                .iter()
+                .map(|variant| {
+                    format!(
+                        "\"{}\"",
+                        variant.serde_name.as_ref().unwrap_or(&variant.name)
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        lines.push(String::from("}"));
+
+        print_json_schema_macro_stdout(&lines);
+        println!("    }}");
+    }
+
+    /// Whether all variants are plain strings, in which case string conversions are generated.
+    fn is_string_enum(&self) -> bool {
+        !self.is_error && self.variants.iter().all(|variant| variant.wraps.is_none())
+    }
+
+    /// Renders `as_str`, `Display` and `FromStr`, using the same strings as serde.
+    fn render_impl_str_stdout(&self, name: &str) {
+        let serde_names = self
+            .variants
+            .iter()
+            .map(|variant| variant.serde_name.as_ref().unwrap_or(&variant.name))
+            .collect::<Vec<_>>();
+
+        println!("impl {name} {{");
+        if self.open.is_some() {
+            println!("    pub fn as_str(&self) -> &str {{");
+        } else {
+            println!("    pub const fn as_str(&self) -> &'static str {{");
+        }
+        println!("        match self {{");
+        for (variant, serde_name) in self.variants.iter().zip(serde_names.iter()) {
+            print_match_arm(
+                &format!("Self::{}", variant.name),
+                &format!("\"{serde_name}\""),
+            );
+        }
+        if self.open.is_some() {
+            println!("            Self::Unknown(value) => value,");
+        }
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+        println!();
+
+        println!("impl core::fmt::Display for {name} {{");
+        println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+        println!("        f.write_str(self.as_str())");
+        println!("    }}");
+        println!("}}");
+        println!();
+
+        println!("impl core::str::FromStr for {name} {{");
+        if self.open.is_some() {
+            println!("    type Err = core::convert::Infallible;");
+        } else {
+            println!("    type Err = ParseEnumError;");
+        }
+        println!();
+        println!("    fn from_str(s: &str) -> Result<Self, Self::Err> {{");
+        println!("        match s {{");
+        for (variant, serde_name) in self.variants.iter().zip(serde_names.iter()) {
+            print_match_arm(
+                &format!("\"{serde_name}\""),
+                &format!("Ok(Self::{})", variant.name),
+            );
+        }
+        if self.open.is_some() {
+            println!("            _ => Ok(Self::Unknown(s.into())),");
+        } else {
+            let accepted = serde_names
+                .iter()
+                .map(|serde_name| format!("\"{serde_name}\""))
+                .collect::<Vec<_>>();
+
+            println!("            _ => Err(ParseEnumError {{");
+            println!("                value: s.into(),");
+            let accepted_line = format!("                accepted: &[{}],", accepted.join(", "));
+            if accepted_line.len() <= MAX_LINE_LENGTH {
+                println!("{accepted_line}");
+            } else {
+                println!("                accepted: &[");
+                for value in accepted.iter() {
+                    println!("                    {value},");
+                }
+                println!("                ],");
+            }
+            println!("            }}),");
+        }
+        println!("        }}");
+        println!("    }}");
+        println!("}}");
+    }
+
+    fn render_impl_serialize_stdout(&self, name: &str) {
+        println!("impl Serialize for {name} {{");
+        println!(
+            "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
+        );
+
+        println!("        serializer.serialize_str(self.as_str())");
+
+        println!("    }}");
+        println!("}}");
+    }
+
+    fn render_impl_deserialize_stdout(&self, name: &str) {
+        println!("impl<'de> Deserialize<'de> for {name} {{");
+        println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
+
+        println!("        let value = String::deserialize(deserializer)?;");
+        println!();
+        println!("        Ok(match value.as_str() {{");
+        for variant in self.variants.iter() {
+            println!(
+                "            \"{}\" => Self::{},",
+                variant.serde_name.as_ref().unwrap_or(&variant.name),
+                variant.name
+            );
+        }
+        println!("            _ => Self::Unknown(value),");
+        println!("        }})");
+
+        println!("    }}");
+        println!("}}");
+    }
+
+    fn with_default_derives(&self) -> IndexSet<String> {
+        let mut derives: IndexSet<_> = self.derives.iter().cloned().collect();
+        derives.insert("Debug".into());
+        derives.insert("Clone".into());
+
+        // Implement `Copy` when no variant wraps other types
+        if self.open.is_none() && !self.variants.iter().any(|variant| variant.wraps.is_some()) {
+            derives.insert("Copy".into());
+        }
+
+        derives.insert("PartialEq".into());
+        derives.insert("Eq".into());
+        if !self.need_custom_serde() {
+            derives.insert("Serialize".into());
+            derives.insert("Deserialize".into());
+        }
+        derives
+    }
+}
+
+impl RustWrapper {
+    pub fn render_stdout(&self, name: &str, json_schema: bool) {
+        // Maps don't implement `Hash`
+        let is_map = self.type_name.starts_with("IndexMap<");
+
+        if self.serializer.is_some() {
+            println!("#[serde_as]");
+        }
+        println!(
+            "#[derive(Debug, Clone, PartialEq, Eq, {}Serialize, Deserialize)]",
+            if is_map { "" } else { "Hash, " }
+        );
+        if json_schema {
+            print_json_schema_derive();
+        }
+        match &self.serializer {
+            Some(serializer) => {
+                let mut field = format!(
+                    "#[serde_as(as = \"{}\")] pub {}",
+                    serializer.as_serde_as(),
+                    self.type_name
+                );
+                if json_schema {
+                    field = format!(
+                        "#[cfg_attr(feature = \"schemars\", schemars(with = \"{}\"))] {}",
+                        get_json_schema_type(serializer.as_serde_as(), &self.type_name),
+                        field
+                    );
+                }
+
+                if name.len() + field.len() + 14 <= MAX_LINE_LENGTH {
+                    println!("pub struct {name}({field});");
+                } else {
+                    println!("pub struct {name}(");
+                    println!("    {field},");
+                    println!(");");
+                }
+            }
+            None => println!("pub struct {}(pub {});", name, self.type_name),
+        }
+    }
+
+    pub fn need_custom_serde(&self) -> bool {
+        false
+    }
+}
+
+impl RustWrapper {
+    fn render_arbitrary_fn_stdout(&self) {
+        let expr = RustFieldType {
+            type_name: self.type_name.clone(),
+            serializer: self
+                .serializer
+                .as_ref()
+                .map(|value| SerializerOverride::SerdeAs(value.as_serde_as().to_owned())),
+        }
+        .arbitrary_expr();
+
+        print_arbitrary_fn_signature(true);
+        println!("        Ok(Self({expr}))");
+        println!("    }}");
+    }
+}
+
+impl RustUnit {
+    pub fn render_stdout(&self, name: &str, json_schema: bool) {
+        if self.need_custom_serde() {
             println!("#[derive(Debug, Clone, PartialEq, Eq)]");
         } else {
             println!("#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]");
+            if json_schema {
+                print_json_schema_derive();
+            }
         }
         println!("pub struct {name};");
     }
@@ -1023,7 +2804,7 @@ impl RustUnit {
     }
 
     pub fn need_custom_serde(&self) -> bool {
-        self.serde_as_obj
+        self.param_structure.is_some()
     }
 
     fn render_impl_serialize_stdout(&self, name: &str) {
@@ -1032,10 +2813,17 @@ impl RustUnit {
             "    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{"
         );
 
-        println!("        use serde::ser::SerializeSeq;");
-        println!();
-        println!("        let seq = serializer.serialize_seq(Some(0))?;");
-        println!("        seq.end()");
+        if self.param_structure == Some(ParamStructure::ByName) {
+            println!("        use serde::ser::SerializeMap;");
+            println!();
+            println!("        let map = serializer.serialize_map(Some(0))?;");
+            println!("        map.end()");
+        } else {
+            println!("        use serde::ser::SerializeSeq;");
+            println!();
+            println!("        let seq = serializer.serialize_seq(Some(0))?;");
+            println!("        seq.end()");
+        }
 
         println!("    }}");
         println!("}}");
@@ -1045,15 +2833,30 @@ impl RustUnit {
         println!("impl<'de> Deserialize<'de> for {name} {{");
         println!("    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{");
 
-        println!("        let elements = Vec::<()>::deserialize(deserializer)?;");
-        println!("        if !elements.is_empty() {{");
-        println!("            return Err(serde::de::Error::custom(\"invalid sequence length\"));");
-        println!("        }}");
-        println!("        Ok(Self)");
+        render_params_visitor_stdout(name, self.param_structure.unwrap_or_default(), &[]);
 
         println!("    }}");
         println!("}}");
     }
+
+    /// Renders a `json_schema` fn for params without any param.
+    fn render_json_schema_fn_stdout(&self) {
+        print_json_schema_fn_signature(false);
+
+        let object = "{ \"type\": \"object\" }";
+        let array = "{ \"type\": \"array\", \"maxItems\": 0 }";
+        match self.param_structure.unwrap_or_default() {
+            ParamStructure::ByName => println!("        schemars::json_schema!({object})"),
+            ParamStructure::ByPosition => println!("        schemars::json_schema!({array})"),
+            ParamStructure::Either => print_json_schema_macro_stdout(&[
+                String::from("{"),
+                format!("    \"anyOf\": [{object}, {array}],"),
+                String::from("}"),
+            ]),
+        }
+
+        println!("    }}");
+    }
 }
 
 impl RustField {
@@ -1083,16 +2886,26 @@ impl RustField {
             if self.serde_flatten {
                 lines.push(format!("{leading_spaces}#[serde(flatten)]"));
             }
-            if let Some(serde_as) = &self.serializer {
+            if self.auto_boxed && !no_arc_wrapping && !is_ref {
+                // Only fields without serializers get boxed automatically
+                lines.push(format!(
+                    "{leading_spaces}#[serde_as(as = \"{}\")]",
+                    if self.optional {
+                        "Option<OwnedPtr<serde_with::Same>>"
+                    } else {
+                        "OwnedPtr<serde_with::Same>"
+                    }
+                ));
+            } else if let Some(serde_as) = &self.serializer {
                 lines.push(match serde_as {
-                    SerializerOverride::Serde(serializer) => {
-                        format!("{leading_spaces}#[serde(with = \"{serializer}\")]")
+                    SerializerOverride::Serde(adapter) => {
+                        format!("{leading_spaces}#[serde(with = \"{}\")]", adapter.module)
                     }
                     SerializerOverride::SerdeAs(serializer) => {
-                        let serializer = if let Some(FixedField {
-                            is_query_version: true,
-                            ..
-                        }) = &self.fixed
+                        let serializer = if self
+                            .fixed
+                            .as_ref()
+                            .is_some_and(|fixed| !fixed.version_offsets.is_empty())
                         {
                             if self.optional {
                                 "Option<UfeHex>".to_owned()
@@ -1115,10 +2928,10 @@ impl RustField {
             }
         }
 
-        let type_name = if let Some(FixedField {
-            is_query_version: true,
-            ..
-        }) = &self.fixed
+        let type_name = if self
+            .fixed
+            .as_ref()
+            .is_some_and(|fixed| !fixed.version_offsets.is_empty())
         {
             if self.optional {
                 "Option<Felt>"
@@ -1151,6 +2964,12 @@ impl RustField {
                 } else {
                     format!("&'a {type_name}")
                 }
+            } else if self.auto_boxed && !no_arc_wrapping {
+                if self.optional && !is_wrapped_field {
+                    format!("Option<OwnedPtr<{type_name}>>")
+                } else {
+                    format!("OwnedPtr<{type_name}>")
+                }
             } else if self.arc_wrap && !no_arc_wrapping {
                 format!("OwnedPtr<{type_name}>")
             } else if self.optional && !is_wrapped_field {
@@ -1164,66 +2983,671 @@ impl RustField {
     }
 }
 
-impl SerializerOverride {
-    pub fn to_optional(&self) -> Self {
-        match self {
-            SerializerOverride::Serde(_) => {
-                todo!("Optional transformation of #[serde(with)] not implemented")
-            }
-            SerializerOverride::SerdeAs(serde_as) => Self::SerdeAs(format!("Option<{serde_as}>")),
+impl RustField {
+    /// Gets the type whose schema describes the JSON of this field, for fields (de)serialized
+    /// through adapters that `schemars` can't see through.
+    fn json_schema_type(&self) -> Option<String> {
+        let serde_as = if self.auto_boxed {
+            "OwnedPtr<serde_with::Same>"
+        } else if let Some(serializer) = &self.serializer {
+            serializer.as_serde_as()
+        } else if self.arc_wrap {
+            return Some(self.type_name.clone());
+        } else {
+            return None;
+        };
+
+        Some(if self.optional {
+            get_json_schema_type(serde_as, &format!("Option<{}>", self.type_name))
+        } else {
+            get_json_schema_type(serde_as, &self.type_name)
+        })
+    }
+
+    /// Type taken by builders for this field, accepting both owned values and pointers to shared
+    /// ones for fields held in an `OwnedPtr`.
+    fn builder_param_type(&self) -> String {
+        if self.arc_wrap || self.auto_boxed {
+            format!("impl Into<OwnedPtr<{}>>", self.type_name)
+        } else {
+            self.type_name.clone()
         }
     }
-}
 
-fn resolve_types(
-    specs: &Specification,
-    flatten_option: &FlattenOption,
-    ignore_types: &[String],
-    allow_unknown_field_types: &[String],
-    fixed_fields: &FixedFieldsOptions,
-    arc_wrapping: &ArcWrappingOptions,
-    additional_derives_types: &AdditionalDerivesOptions,
-) -> Result<TypeResolutionResult> {
-    let mut types = vec![];
-    let mut aliases = vec![];
-    let mut req_types: Vec<RustType> = vec![];
-    let mut not_implemented_types = vec![];
+    /// Expression generating an arbitrary value of this field out of the `Unstructured` in `u`.
+    fn arbitrary_expr(&self) -> String {
+        let value_expr = RustFieldType {
+            type_name: self.type_name.clone(),
+            serializer: match &self.serializer {
+                // Params keep serializers of optional fields unwrapped for their field wrappers
+                Some(SerializerOverride::SerdeAs(serde_as))
+                    if self.optional && !serde_as.starts_with("Option<") =>
+                {
+                    Some(SerializerOverride::SerdeAs(format!("Option<{serde_as}>")))
+                }
+                serializer => serializer.clone(),
+            },
+        }
+        .arbitrary_expr();
 
-    let flatten_only_types = get_flatten_only_schemas(specs, flatten_option);
+        if self.auto_boxed && self.optional {
+            String::from("u.arbitrary::<Option<_>>()?.map(OwnedPtr::new)")
+        } else if self.arc_wrap || self.auto_boxed {
+            format!("OwnedPtr::new({value_expr})")
+        } else {
+            value_expr
+        }
+    }
+}
 
-    for (name, entity) in specs.components.schemas.iter() {
-        let rusty_name = to_starknet_rs_name(name);
+impl SerializerOverride {
+    pub fn to_optional(&self) -> Self {
+        Self::SerdeAs(format!("Option<{}>", self.as_serde_as()))
+    }
 
-        let title = entity.title();
-        let description = match entity.description() {
-            Some(description) => Some(description),
-            None => entity.summary(),
-        };
+    pub fn to_array(&self) -> Self {
+        Self::SerdeAs(format!("Vec<{}>", self.as_serde_as()))
+    }
 
-        // Explicitly ignored types
-        if ignore_types.contains(name) {
-            continue;
+    /// Uses the `#[serde(with)]` module of the given name, which must have a generated `serde_as`
+    /// adapter for the serializer to be composed into `Option` and `Vec` positions.
+    fn serde_with(module: &str) -> Result<Self> {
+        match SerdeWithAdapter::find(module) {
+            Some(adapter) => Ok(Self::Serde(adapter)),
+            None => anyhow::bail!("No serde_as adapter for #[serde(with)] module {}", module),
         }
+    }
 
-        // Manual override exists
-        if get_field_type_override(name).is_some() {
-            continue;
+    /// Gets the `serde_as` form of the serializer, switching `#[serde(with)]` modules to their
+    /// generated adapters.
+    fn as_serde_as(&self) -> &str {
+        match self {
+            SerializerOverride::Serde(adapter) => adapter.adapter,
+            SerializerOverride::SerdeAs(serde_as) => serde_as,
         }
+    }
+}
 
-        if flatten_only_types.contains(name) {
-            continue;
+impl<'a> InlineTypes<'a> {
+    pub fn new(specs: &Specification, naming: &'a InlineTypeNamingOptions) -> Self {
+        Self {
+            naming,
+            reserved_names: specs
+                .components
+                .schemas
+                .keys()
+                .map(|name| to_starknet_rs_name(name))
+                .collect(),
+            registered: IndexMap::new(),
+            pending: VecDeque::new(),
         }
+    }
 
-        let derives = additional_derives_types
-            .find_additional_derives(&rusty_name)
-            .unwrap_or_default();
+    /// Registers an anonymous schema used as field `field_name` in type `type_name`, returning
+    /// the name of the type synthesized for it.
+    pub fn register(
+        &mut self,
+        type_name: &str,
+        field_name: &str,
+        schema: &Schema,
+    ) -> Result<String> {
+        let synthesized_name = self
+            .naming
+            .find_type_name(type_name, field_name)
+            .unwrap_or_else(|| format!("{}{}", type_name, to_pascal_case(field_name)));
+
+        if let Some(existing) = self.registered.get(&synthesized_name) {
+            if existing != schema {
+                anyhow::bail!(
+                    "Conflicting schemas for synthesized type {}. \
+                    Use `inline_type_names` to rename field `{}` of `{}`",
+                    synthesized_name,
+                    field_name,
+                    type_name
+                );
+            }
+        } else {
+            if self.reserved_names.contains(&synthesized_name) {
+                anyhow::bail!(
+                    "Synthesized type {} conflicts with an existing type. \
+                    Use `inline_type_names` to rename field `{}` of `{}`",
+                    synthesized_name,
+                    field_name,
+                    type_name
+                );
+            }
 
-        let content = match schema_to_rust_type_kind(
-            specs,
+            self.registered
+                .insert(synthesized_name.clone(), schema.to_owned());
+            self.pending.push_back(synthesized_name.clone());
+        }
+
+        Ok(synthesized_name)
+    }
+
+    pub fn next_pending(&mut self) -> Option<(String, Schema)> {
+        let name = self.pending.pop_front()?;
+        let schema = self.registered[&name].clone();
+        Some((name, schema))
+    }
+}
+
+impl PatternRegistry {
+    pub fn new(specs: &Specification) -> Self {
+        let mut schema_patterns = HashMap::new();
+
+        for name in specs.components.schemas.keys() {
+            // Follows references to the schema actually defining the pattern
+            let mut target_name = name.as_str();
+            let mut visited = HashSet::new();
+            while let Some(Schema::Ref(reference)) = specs.components.schemas.get(target_name) {
+                if !visited.insert(target_name) {
+                    break;
+                }
+                target_name = reference.name();
+            }
+
+            if let Some(Schema::Primitive(Primitive::String(StringPrimitive {
+                pattern: Some(pattern),
+                ..
+            }))) = specs.components.schemas.get(target_name)
+            {
+                schema_patterns.insert(
+                    name.to_owned(),
+                    PatternRule {
+                        marker: format!("{}Pattern", to_starknet_rs_name(target_name)),
+                        rule: format!("#/components/schemas/{target_name}"),
+                        pattern: pattern.to_owned(),
+                    },
+                );
+            }
+        }
+
+        Self {
+            schema_patterns,
+            schema_names: specs
+                .components
+                .schemas
+                .keys()
+                .map(|name| (to_starknet_rs_name(name), name.to_owned()))
+                .collect(),
+            used: IndexMap::new(),
+        }
+    }
+
+    /// Gets the pattern marker for a named schema, if it has a pattern.
+    pub fn for_schema(&mut self, schema_name: &str) -> Option<String> {
+        let rule = self.schema_patterns.get(schema_name)?.to_owned();
+        let marker = rule.marker.clone();
+        self.used.entry(marker.clone()).or_insert(rule);
+        Some(marker)
+    }
+
+    /// Gets the pattern marker for the generated type `type_name` defined by a string schema.
+    pub fn for_type(&mut self, type_name: &str, pattern: &str) -> String {
+        match self.schema_names.get(type_name).cloned() {
+            Some(schema_name) => self.for_schema(&schema_name).unwrap_or_else(|| {
+                self.register(
+                    format!("{type_name}Pattern"),
+                    format!("#/components/schemas/{schema_name}"),
+                    pattern,
+                )
+            }),
+            None => self.register(
+                format!("{type_name}Pattern"),
+                format!("`{type_name}`"),
+                pattern,
+            ),
+        }
+    }
+
+    /// Gets the pattern marker for an inline string schema used as field `field_name` in type
+    /// `type_name`.
+    pub fn for_field(&mut self, type_name: &str, field_name: &str, pattern: &str) -> String {
+        self.register(
+            format!("{}{}Pattern", type_name, to_pascal_case(field_name)),
+            format!("`{field_name}` of `{type_name}`"),
+            pattern,
+        )
+    }
+
+    fn register(&mut self, marker: String, rule: String, pattern: &str) -> String {
+        self.used.entry(marker.clone()).or_insert(PatternRule {
+            marker: marker.clone(),
+            rule,
+            pattern: pattern.to_owned(),
+        });
+        marker
+    }
+}
+
+impl PatternRule {
+    pub fn render_stdout(&self) {
+        println!("struct {};", self.marker);
+        println!();
+        println!("#[cfg(feature = \"validate_patterns\")]");
+        println!("impl SpecPattern for {} {{", self.marker);
+        println!("    const RULE: &'static str = {:?};", self.rule);
+        let pattern_line = format!("    const PATTERN: &'static str = {:?};", self.pattern);
+        if pattern_line.len() <= MAX_LINE_LENGTH {
+            println!("{pattern_line}");
+        } else {
+            println!("    const PATTERN: &'static str =");
+            println!("        {:?};", self.pattern);
+        }
+        println!();
+        println!("    fn regex() -> &'static regex::Regex {{");
+        println!(
+            "        static REGEX: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();"
+        );
+        println!("        REGEX.get_or_init(|| regex::Regex::new(Self::PATTERN).unwrap())");
+        println!("    }}");
+        println!("}}");
+    }
+}
+
+impl<'a> SampleSynthesizer<'a> {
+    pub fn new(
+        types: &[&'a RustType],
+        aliases: &'a [RustAlias],
+        patterns: &'a [PatternRule],
+    ) -> Self {
+        Self {
+            types: types
+                .iter()
+                .map(|rust_type| (rust_type.name.as_str(), *rust_type))
+                .collect(),
+            aliases: aliases
+                .iter()
+                .map(|alias| (alias.name.as_str(), alias.content.src_name.as_str()))
+                .collect(),
+            patterns: patterns
+                .iter()
+                .map(|pattern| (pattern.marker.as_str(), pattern.pattern.as_str()))
+                .collect(),
+            samples: HashMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    /// Gets the sample of a generated type, or `None` when it depends on types without samples,
+    /// like the ones implemented manually.
+    pub fn for_type(&mut self, name: &str) -> Option<serde_json::Value> {
+        if let Some(sample) = self.samples.get(name) {
+            return sample.clone();
+        }
+        if let Some(src_name) = self.aliases.get(name).copied() {
+            return self.for_type(src_name);
+        }
+
+        let rust_type = *self.types.get(name)?;
+
+        // Optional fields and arrays are left empty instead of recursing
+        if !self.visiting.insert(name.to_owned()) {
+            return None;
+        }
+
+        let sample = match &rust_type.content {
+            RustTypeKind::Struct(content) => {
+                let values = self.field_samples(content)?;
+                match content.param_structure {
+                    Some(ParamStructure::ByPosition) => Some(Self::to_positional(values)),
+                    _ => Some(Self::to_object(values)),
+                }
+            }
+            RustTypeKind::Enum(content) => {
+                let variant = content.variants.first()?;
+                let variant_name = variant.serde_name.as_ref().unwrap_or(&variant.name);
+                match &variant.wraps {
+                    Some(inner) => {
+                        let value = self.for_field_type(
+                            &inner.type_name,
+                            inner.serializer.as_ref().map(|value| value.as_serde_as()),
+                        )?;
+                        Some(serde_json::json!({ variant_name: value }))
+                    }
+                    None => Some(serde_json::Value::String(variant_name.to_owned())),
+                }
+            }
+            RustTypeKind::Wrapper(content) => self.for_field_type(
+                &content.type_name,
+                content.serializer.as_ref().map(|value| value.as_serde_as()),
+            ),
+            RustTypeKind::Unit(content) => Some(match content.param_structure {
+                Some(ParamStructure::ByName) => serde_json::json!({}),
+                Some(_) => serde_json::json!([]),
+                None => serde_json::Value::Null,
+            }),
+        };
+
+        self.visiting.remove(name);
+        self.samples.insert(name.to_owned(), sample.clone());
+        sample
+    }
+
+    /// Gets the sample of method params passed by position, regardless of their param structure.
+    pub fn for_positional_params(&mut self, content: &RustStruct) -> Option<serde_json::Value> {
+        self.field_samples(content).map(Self::to_positional)
+    }
+
+    /// Gets a value of the type of a fixed field different from its fixed value, preferring other
+    /// variants of enums.
+    pub fn wrong_fixed_value(
+        &self,
+        field: &RustField,
+        fixed: &serde_json::Value,
+    ) -> serde_json::Value {
+        if let Some(RustTypeKind::Enum(content)) = self
+            .types
+            .get(field.type_name.as_str())
+            .map(|value| &value.content)
+        {
+            if let Some(variant) = content.variants.iter().find(|variant| {
+                fixed.as_str() != Some(variant.serde_name.as_ref().unwrap_or(&variant.name))
+            }) {
+                return serde_json::Value::String(
+                    variant
+                        .serde_name
+                        .as_ref()
+                        .unwrap_or(&variant.name)
+                        .to_owned(),
+                );
+            }
+        }
+
+        match fixed {
+            serde_json::Value::String(value) if value.starts_with("0x") => {
+                serde_json::json!("0xdead")
+            }
+            serde_json::Value::Number(value) => serde_json::json!(value.as_u64().unwrap_or(0) + 1),
+            serde_json::Value::Bool(value) => serde_json::json!(!value),
+            _ => serde_json::json!("INVALID"),
+        }
+    }
+
+    /// Gets the sample of each field along with the field, leaving out optional fields without
+    /// samples.
+    fn field_samples<'b>(
+        &mut self,
+        content: &'b RustStruct,
+    ) -> Option<Vec<(&'b RustField, Option<serde_json::Value>)>> {
+        // Fields that must not all be present are sampled without the last one
+        let left_out_field = content.excluded_fields.last();
+
+        let mut values = vec![];
+        for field in content.fields.iter() {
+            let key = field.serde_rename.as_ref().unwrap_or(&field.name);
+            if field.optional && left_out_field == Some(key) {
+                continue;
+            }
+
+            let serializer = field.serializer.as_ref().map(|value| match value {
+                SerializerOverride::Serde(adapter) => adapter.module,
+                SerializerOverride::SerdeAs(serde_as) => serde_as.as_str(),
+            });
+            let value = match &field.fixed {
+                Some(fixed) => self.for_fixed_value(&fixed.value, &field.type_name, serializer),
+                None => self.for_field_type(&field.type_name, serializer),
+            };
+            if value.is_none() && !field.optional {
+                return None;
+            }
+
+            values.push((field, value));
+        }
+
+        Some(values)
+    }
+
+    fn to_object(values: Vec<(&RustField, Option<serde_json::Value>)>) -> serde_json::Value {
+        let mut object = serde_json::Map::new();
+        for (field, value) in values {
+            match value {
+                Some(serde_json::Value::Object(value)) if field.serde_flatten => {
+                    object.extend(value);
+                }
+                Some(value) => {
+                    let key = field.serde_rename.as_ref().unwrap_or(&field.name);
+                    object.insert(key.to_owned(), value);
+                }
+                None => {}
+            }
+        }
+        serde_json::Value::Object(object)
+    }
+
+    fn to_positional(values: Vec<(&RustField, Option<serde_json::Value>)>) -> serde_json::Value {
+        serde_json::Value::Array(
+            values
+                .into_iter()
+                .map(|(_, value)| value.unwrap_or_default())
+                .collect(),
+        )
+    }
+
+    /// Gets the JSON of a fixed field value, which is either a string literal or a reference to
+    /// an integer literal, a `Felt` constant or an enum variant.
+    fn for_fixed_value(
+        &self,
+        value: &str,
+        type_name: &str,
+        serializer: Option<&str>,
+    ) -> Option<serde_json::Value> {
+        let value = value.trim_start_matches('&');
+        let literal = match value.split_once("::") {
+            Some(("Felt", constant)) => serde_json::json!(["ZERO", "ONE", "TWO", "THREE"]
+                .iter()
+                .position(|name| *name == constant)?),
+            Some((enum_name, variant_name)) => {
+                let RustTypeKind::Enum(content) = &self.types.get(enum_name)?.content else {
+                    return None;
+                };
+                let variant = content
+                    .variants
+                    .iter()
+                    .find(|variant| variant.name == variant_name)?;
+                return Some(serde_json::Value::String(
+                    variant
+                        .serde_name
+                        .as_ref()
+                        .unwrap_or(&variant.name)
+                        .to_owned(),
+                ));
+            }
+            None => serde_json::from_str(value).ok()?,
+        };
+
+        let is_hex = type_name == "Felt"
+            || serializer
+                .is_some_and(|value| value.contains("UfeHex") || value.contains("NumAsHex"));
+        Some(match literal.as_u64() {
+            Some(literal) if is_hex => serde_json::Value::String(format!("{literal:#x}")),
+            _ => literal,
+        })
+    }
+
+    /// Gets the sample of a field type as (de)serialized with the `serde_as` adapter or
+    /// `#[serde(with)]` module `serializer`.
+    fn for_field_type(
+        &mut self,
+        type_name: &str,
+        serializer: Option<&str>,
+    ) -> Option<serde_json::Value> {
+        // Optional and boxed values are sampled as the values they hold
+        for wrapper in ["Option", "OwnedPtr"] {
+            if let Some(inner) = strip_generic(type_name, wrapper) {
+                return self.for_field_type(
+                    inner,
+                    serializer.map(|value| strip_generic(value, wrapper).unwrap_or(value)),
+                );
+            }
+        }
+        let serializer = serializer.map(|value| strip_generic(value, "Option").unwrap_or(value));
+
+        if let Some(item_type) = strip_generic(type_name, "Vec") {
+            let item_serializer = serializer.and_then(|value| {
+                strip_generic(value, "Vec").or_else(|| value.strip_prefix('[')?.strip_suffix(']'))
+            });
+            if serializer.is_none() || item_serializer.is_some() {
+                return Some(serde_json::Value::Array(
+                    self.for_field_type(item_type, item_serializer)
+                        .into_iter()
+                        .collect(),
+                ));
+            }
+        }
+
+        match serializer {
+            Some(serializer) => {
+                if let Some(args) = strip_generic(serializer, "Validated") {
+                    let (marker, inner) = match args.split_once(", ") {
+                        Some((marker, inner)) => (marker, Some(inner)),
+                        None => (args, None),
+                    };
+                    let candidates = match inner {
+                        Some("UfeHex" | "NumAsHex") => &HEX_PATTERN_SAMPLES[..],
+                        _ => &STRING_PATTERN_SAMPLES[..],
+                    };
+                    let regex = Regex::new(self.patterns.get(marker)?).ok()?;
+                    return candidates
+                        .iter()
+                        .find(|candidate| regex.is_match(candidate))
+                        .map(|candidate| serde_json::json!(candidate));
+                }
+
+                if let Some(bounds) = strip_generic(serializer, "Bounded") {
+                    let (min, max) = bounds.split_once(", ")?;
+                    let value = 1.clamp(min.parse::<i128>().ok()?, max.parse::<i128>().ok()?);
+                    return serde_json::to_value(value as i64).ok();
+                }
+
+                match serializer {
+                    "UfeHex" | "NumAsHex" => Some(serde_json::json!("0x1")),
+                    "MerkleNodeMap" => Some(serde_json::json!([])),
+                    "Base64" | "base64" => Some(serde_json::json!("AAAA")),
+                    // Other adapters like `serde_with::Same` keep the representation of the type
+                    _ => self.for_field_type(type_name, None),
+                }
+            }
+            None => match type_name {
+                "Felt" => Some(serde_json::json!("0x1")),
+                "bool" => Some(serde_json::json!(true)),
+                "String" => Some(serde_json::json!("sample")),
+                "serde_json::Value" => Some(serde_json::json!({})),
+                _ if type_name.starts_with("IndexMap<") => Some(serde_json::json!({})),
+                _ if INTEGER_TYPES.iter().any(|integer_type| {
+                    integer_type.type_name == type_name
+                        || integer_type.non_zero_type_name == type_name
+                }) =>
+                {
+                    Some(serde_json::json!(1))
+                }
+                _ => self.for_type(type_name),
+            },
+        }
+    }
+}
+
+impl SerdeWithAdapter {
+    fn find(module: &str) -> Option<Self> {
+        SERDE_WITH_ADAPTERS
+            .iter()
+            .find(|adapter| adapter.module == module)
+            .copied()
+    }
+
+    fn is_used(&self, types: &[&RustType]) -> bool {
+        is_serde_as_adapter_used(types, self.adapter)
+    }
+
+    pub fn render_stdout(&self) {
+        println!("struct {};", self.adapter);
+        println!();
+        println!(
+            "impl serde_with::SerializeAs<{}> for {} {{",
+            self.type_name, self.adapter
+        );
+        println!(
+            "    fn serialize_as<S: Serializer>(value: &{}, serializer: S) -> Result<S::Ok, S::Error> {{",
+            self.type_name
+        );
+        println!("        {}::serialize(value, serializer)", self.module);
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!(
+            "impl<'de> serde_with::DeserializeAs<'de, {}> for {} {{",
+            self.type_name, self.adapter
+        );
+        println!(
+            "    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<{}, D::Error> {{",
+            self.type_name
+        );
+        println!("        {}::deserialize(deserializer)", self.module);
+        println!("    }}");
+        println!("}}");
+    }
+}
+
+fn resolve_types(
+    specs: &Specification,
+    options: &ProfileOptions,
+    validate_patterns: bool,
+) -> Result<TypeResolutionResult> {
+    let flatten_option = &options.flatten_options;
+    let additional_derives_types = &options.additional_derives_types;
+
+    let mut types = vec![];
+    let mut aliases = vec![];
+    let mut req_types: Vec<RustType> = vec![];
+    let mut method_results = IndexMap::new();
+    let mut not_implemented_types = vec![];
+
+    let flatten_only_types = get_flatten_only_schemas(specs, flatten_option);
+    let mut context = ResolutionContext {
+        inline_types: InlineTypes::new(specs, &options.inline_type_names),
+        patterns: if validate_patterns {
+            Some(PatternRegistry::new(specs))
+        } else {
+            None
+        },
+    };
+
+    for (name, entity) in specs.components.schemas.iter() {
+        let rusty_name = to_starknet_rs_name(name);
+
+        let title = entity.title();
+        let description = match entity.description() {
+            Some(description) => Some(description),
+            None => entity.summary(),
+        };
+
+        // Explicitly ignored types
+        if options.ignore_types.contains(name) {
+            continue;
+        }
+
+        // Manual override exists
+        if get_field_type_override(name).is_some() {
+            continue;
+        }
+
+        if flatten_only_types.contains(name) {
+            continue;
+        }
+
+        let derives = additional_derives_types
+            .find_additional_derives(&rusty_name)
+            .unwrap_or_default();
+
+        let content = match schema_to_rust_type_kind(
+            specs,
             entity,
-            allow_unknown_field_types.contains(name),
+            &rusty_name,
+            options.allow_unknown_field_types.contains(name),
             flatten_option,
             derives,
+            &mut context,
         )? {
             Some(content) => content,
             None => {
@@ -1236,12 +3660,16 @@ fn resolve_types(
 
         match content {
             SchemaToRustTypeResult::Type(mut content) => {
-                if let RustTypeKind::Struct(inner) = &mut content {
-                    for field in inner.fields.iter_mut() {
-                        field.fixed = fixed_fields.find_fixed_field(&rusty_name, &field.name);
-                        field.arc_wrap = arc_wrapping.in_field_wrapped(&rusty_name, &field.name);
-                    }
+                if options.capture_unknown_field_types.contains(name) {
+                    content.capture_unknown_fields(&rusty_name)?;
+                }
+                if options.extra_ref_types.find_extra_ref_type(name).is_some() {
+                    content.add_ref_type(&rusty_name)?;
+                }
+                if let Some(open) = options.open_enum_types.find_open_enum(&rusty_name) {
+                    content.open_enum(&rusty_name, open)?;
                 }
+                content.apply_field_options(&rusty_name, options)?;
 
                 types.push(RustType {
                     title: title.map(|value| to_starknet_rs_doc(value, true)),
@@ -1263,6 +3691,7 @@ fn resolve_types(
         name: String::from("StarknetError"),
         content: RustTypeKind::Enum(RustEnum {
             is_error: true,
+            open: None,
             variants: specs
                 .components
                 .errors
@@ -1275,21 +3704,21 @@ fn resolve_types(
                         error_text: Some(err.message.clone()),
                         error_code: Some(err.code),
                         wraps: match &err.data {
-                            Some(err_data) => match err_data {
-                                Schema::Ref(value) => Some(RustFieldType {
-                                    type_name: to_starknet_rs_name(value.name()),
-                                    serializer: None,
-                                }),
-                                Schema::Primitive(_) => Some(get_rust_type_for_field(err_data)?),
-                                Schema::OneOf(_) => anyhow::bail!(
-                                    "Anonymous oneOf types should not be used for error data"
-                                ),
-                                Schema::AllOf(_) => anyhow::bail!(
-                                    "Anonymous allOf types should not be used for error data"
-                                ),
-                            },
+                            Some(Schema::Ref(value)) => Some(RustFieldType {
+                                type_name: to_starknet_rs_name(value.name()),
+                                serializer: None,
+                            }),
+                            // Anonymous object, `oneOf` and `allOf` schemas get types named after
+                            // the error, like `TransactionExecutionErrorData`
+                            Some(err_data) => Some(get_rust_type_for_field(
+                                err_data,
+                                &to_starknet_rs_name(name),
+                                "data",
+                                &mut context,
+                            )?),
                             None => None,
                         },
+                        boxed: false,
                     }),
                     ErrorType::Reference(_) => todo!("Error redirection not implemented"),
                 })
@@ -1301,11 +3730,52 @@ fn resolve_types(
     });
 
     // Request/response types
+    req_types.push(RustType {
+        title: Some(String::from("JSON-RPC method names")),
+        description: None,
+        name: String::from("Method"),
+        content: RustTypeKind::Enum(RustEnum {
+            is_error: false,
+            open: None,
+            variants: specs
+                .methods
+                .iter()
+                .map(|method| RustVariant {
+                    description: None,
+                    name: to_method_variant_name(&method.name),
+                    serde_name: Some(method.name.clone()),
+                    error_text: None,
+                    error_code: None,
+                    wraps: None,
+                    boxed: false,
+                })
+                .collect(),
+            derives: vec![String::from("Hash")],
+        }),
+    });
+
     for method in specs.methods.iter() {
+        let variant_name = to_method_variant_name(&method.name);
+        let rusty_name = format!("{variant_name}Request");
+        let param_structure = get_param_structure(method, options);
+
+        // Anonymous results get types named after the method, like `GetBlockWithTxsResult`
+        if let Some(result) = &method.result {
+            let result_type =
+                get_rust_type_for_field(&result.schema, &variant_name, "result", &mut context)?;
+            method_results.insert(method.name.clone(), result_type);
+        }
+
         let mut request_fields = vec![];
 
         for param in method.params.iter() {
-            let field_type = get_rust_type_for_field(&param.schema)?;
+            let param_schema = match param.schema.non_null_option() {
+                Some(option) if !param.required => option,
+                _ => &param.schema,
+            };
+
+            let field_type =
+                get_rust_type_for_field(param_schema, &rusty_name, &param.name, &mut context)?;
 
             request_fields.push(RustField {
                 description: param.description.clone(),
@@ -1313,6 +3783,7 @@ fn resolve_types(
                 optional: !param.required,
                 fixed: None,
                 arc_wrap: false,
+                auto_boxed: false,
                 type_name: field_type.type_name,
                 serde_rename: None,
                 serde_flatten: false,
@@ -1320,25 +3791,24 @@ fn resolve_types(
             });
         }
 
-        let rusty_name = format!(
-            "{}Request",
-            to_starknet_rs_name(&camel_to_snake_case(
-                method.name.trim_start_matches("starknet_")
-            ))
-        );
-
         let request_type = RustType {
             title: Some(format!("Request for method {}", method.name)),
             description: None,
             name: rusty_name.clone(),
             content: if request_fields.is_empty() {
-                RustTypeKind::Unit(RustUnit { serde_as_obj: true })
+                RustTypeKind::Unit(RustUnit {
+                    param_structure: Some(param_structure),
+                })
             } else {
                 RustTypeKind::Struct(RustStruct {
                     allow_unknown_fields: false,
-                    serde_as_obj: true,
+                    param_structure: Some(param_structure),
                     extra_ref_type: true,
+                    builder_defaults: None,
+                    borrowed_fields: None,
                     fields: request_fields,
+                    excluded_fields: vec![],
+                    version_offsets: vec![],
                     derives: additional_derives_types
                         .find_additional_derives(&rusty_name)
                         .unwrap_or_default(),
@@ -1349,25 +3819,394 @@ fn resolve_types(
         req_types.push(request_type);
     }
 
+    // Types synthesized for anonymous schemas, which can themselves contain anonymous schemas
+    while let Some((rusty_name, entity)) = context.inline_types.next_pending() {
+        let derives = additional_derives_types
+            .find_additional_derives(&rusty_name)
+            .unwrap_or_default();
+
+        let mut content = match schema_to_rust_type_kind(
+            specs,
+            &entity,
+            &rusty_name,
+            false,
+            flatten_option,
+            derives,
+            &mut context,
+        )? {
+            Some(SchemaToRustTypeResult::Type(content)) => content,
+            Some(SchemaToRustTypeResult::Alias(_)) => {
+                anyhow::bail!("Unexpected alias synthesized for inline type {rusty_name}")
+            }
+            None => {
+                eprintln!(
+                    "OneOf enum generation not implemented. Enum not generated for {rusty_name}"
+                );
+
+                not_implemented_types.push(rusty_name);
+                continue;
+            }
+        };
+
+        content.apply_field_options(&rusty_name, options)?;
+
+        types.push(RustType {
+            title: entity.title().map(|value| to_starknet_rs_doc(value, true)),
+            description: entity
+                .description()
+                .or_else(|| entity.summary())
+                .map(|value| to_starknet_rs_doc(value, true)),
+            name: rusty_name,
+            content,
+        });
+    }
+
+    // Results can't be decoded to types that couldn't be generated, so they're left as raw JSON
+    for (method_name, result_type) in method_results.iter_mut() {
+        let missing_type = not_implemented_types.iter().find(|name| {
+            result_type
+                .type_name
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .any(|part| part == name.as_str())
+        });
+        if let Some(missing_type) = missing_type {
+            eprintln!(
+                "Result type {missing_type} not generated. Result of {method_name} decoded as `serde_json::Value`"
+            );
+
+            *result_type = RustFieldType {
+                type_name: String::from("serde_json::Value"),
+                serializer: None,
+            };
+        }
+    }
+
+    // Recursion can go through any type, so boxing is only decided once all types are resolved
+    box_recursive_fields(specs, &mut types, options);
+    box_large_variants(&mut types, options);
+
+    // Field types are only known to have `Borrowed` variants once all types are resolved
+    let borrowed_types = types
+        .iter()
+        .filter(|item| {
+            options
+                .borrowed_types
+                .find_borrowed_type(&item.name)
+                .is_some()
+        })
+        .map(|item| item.name.clone())
+        .collect::<Vec<_>>();
+    for item in types.iter_mut() {
+        if let Some(borrowed) = options.borrowed_types.find_borrowed_type(&item.name) {
+            item.content
+                .add_borrowed_type(&item.name, &borrowed.raw_fields, &borrowed_types)?;
+        }
+        if let Some(builder) = options.builder_types.find_builder_type(&item.name) {
+            item.content.add_builder(&item.name, &builder.defaults)?;
+        }
+    }
+
     // Sorting the types makes it easier to check diffs in generated code.
     types.sort_by_key(|item| item.name.to_owned());
     req_types.sort_by_key(|item| item.name.to_owned());
     not_implemented_types.sort();
 
+    let mut patterns = context
+        .patterns
+        .map(|registry| registry.used.into_values().collect::<Vec<_>>())
+        .unwrap_or_default();
+    patterns.sort_by_key(|item| item.marker.to_owned());
+
     Ok(TypeResolutionResult {
         model_types: types,
         aliases,
         request_response_types: req_types,
+        method_results,
         not_implemented: not_implemented_types,
+        patterns,
     })
 }
 
-fn schema_to_rust_type_kind(
-    specs: &Specification,
+/// Boxes struct fields holding their own type by value, directly or through other types, which
+/// would otherwise make the type infinitely sized.
+fn box_recursive_fields(specs: &Specification, types: &mut [RustType], options: &ProfileOptions) {
+    // Types not generated, like the ones implemented manually, are followed through their schemas
+    let mut edges = specs
+        .components
+        .schemas
+        .iter()
+        .map(|(name, schema)| {
+            let mut refs = vec![];
+            get_schema_value_refs(schema, &mut refs);
+
+            (
+                to_starknet_rs_name(name),
+                refs.iter()
+                    .map(|item| to_starknet_rs_name(item))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    for item in types.iter() {
+        edges.insert(item.name.clone(), item.content.value_type_names());
+    }
+
+    for item in types.iter_mut() {
+        let RustTypeKind::Struct(inner) = &mut item.content else {
+            continue;
+        };
+
+        for field in inner.fields.iter_mut() {
+            if field.arc_wrap
+                || field.serializer.is_some()
+                || options
+                    .unboxed_types
+                    .is_field_unboxed(&item.name, &field.name)
+            {
+                continue;
+            }
+
+            if let Some(type_name) = get_value_type_name(&field.type_name) {
+                if type_name == item.name || type_reaches(&edges, type_name, &item.name) {
+                    field.auto_boxed = true;
+                }
+            }
+        }
+    }
+}
+
+/// Boxes the largest variant of enums for as long as it's larger than the next one by at least
+/// [LARGE_VARIANT_SIZE_DIFFERENCE].
+fn box_large_variants(types: &mut [RustType], options: &ProfileOptions) {
+    let kinds = types
+        .iter()
+        .map(|item| (item.name.clone(), item.content.clone()))
+        .collect::<HashMap<_, _>>();
+    let mut sizes = HashMap::new();
+
+    for item in types.iter_mut() {
+        let RustTypeKind::Enum(inner) = &mut item.content else {
+            continue;
+        };
+
+        for variant in inner.variants.iter_mut() {
+            if variant.wraps.is_some()
+                && options
+                    .arc_wrapped_types
+                    .in_field_wrapped(&item.name, &variant.name)
+            {
+                variant.boxed = true;
+            }
+        }
+
+        loop {
+            let mut variant_sizes = inner
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(ind, variant)| {
+                    let size = match &variant.wraps {
+                        Some(_) if variant.boxed => POINTER_SIZE,
+                        Some(wraps) => estimate_type_size(&wraps.type_name, &kinds, &mut sizes),
+                        None => 0,
+                    };
+                    (size, ind)
+                })
+                .collect::<Vec<_>>();
+            if inner.open.is_some() {
+                variant_sizes.push((estimate_type_size("String", &kinds, &mut sizes), usize::MAX));
+            }
+            variant_sizes.sort();
+
+            let Some(&(largest, ind_largest)) = variant_sizes.last() else {
+                break;
+            };
+            let second_largest = match variant_sizes.len() {
+                1 => 0,
+                len => variant_sizes[len - 2].0,
+            };
+
+            if largest - second_largest < LARGE_VARIANT_SIZE_DIFFERENCE || ind_largest == usize::MAX
+            {
+                break;
+            }
+
+            let variant = &mut inner.variants[ind_largest];
+            if variant.boxed
+                || options
+                    .unboxed_types
+                    .is_field_unboxed(&item.name, &variant.name)
+            {
+                break;
+            }
+            variant.boxed = true;
+        }
+    }
+}
+
+/// Estimates the size of a type in bytes, ignoring padding as only large differences matter.
+/// Options only take no extra space when wrapping `OwnedPtr` or `NonZero` integers, so the
+/// estimate is an upper bound for types with other niches. Types not generated, like the ones
+/// implemented manually, are assumed to be as large as a `String`.
+fn estimate_type_size(
+    type_name: &str,
+    types: &HashMap<String, RustTypeKind>,
+    sizes: &mut HashMap<String, usize>,
+) -> usize {
+    if let Some(inner) = type_name
+        .strip_prefix("Option<")
+        .and_then(|value| value.strip_suffix('>'))
+    {
+        return estimate_option_size(inner, types, sizes);
+    }
+    if type_name.starts_with("OwnedPtr<") {
+        return POINTER_SIZE;
+    }
+
+    match type_name {
+        "bool" | "u8" | "i8" | "core::num::NonZeroU8" | "core::num::NonZeroI8" => return 1,
+        "u16" | "i16" | "core::num::NonZeroU16" | "core::num::NonZeroI16" => return 2,
+        "u32" | "i32" | "core::num::NonZeroU32" | "core::num::NonZeroI32" => return 4,
+        "u64" | "i64" | "core::num::NonZeroU64" | "core::num::NonZeroI64" => return 8,
+        "u128" | "i128" => return 16,
+        "Felt" => return 32,
+        _ => {}
+    }
+
+    if let Some(size) = sizes.get(type_name) {
+        return *size;
+    }
+    let Some(kind) = types.get(type_name) else {
+        return 3 * POINTER_SIZE;
+    };
+
+    // Recursive references end up boxed
+    sizes.insert(type_name.to_owned(), POINTER_SIZE);
+
+    let size = match kind {
+        RustTypeKind::Struct(inner) => {
+            inner
+                .fields
+                .iter()
+                .filter(|field| field.fixed.is_none())
+                .map(|field| {
+                    if field.arc_wrap || field.auto_boxed {
+                        POINTER_SIZE
+                    } else if field.optional {
+                        estimate_option_size(&field.type_name, types, sizes)
+                    } else {
+                        estimate_type_size(&field.type_name, types, sizes)
+                    }
+                })
+                .sum::<usize>()
+                + inner.version_offsets.len()
+        }
+        RustTypeKind::Enum(inner) => {
+            POINTER_SIZE
+                + inner
+                    .variants
+                    .iter()
+                    .map(|variant| match &variant.wraps {
+                        Some(_) if variant.boxed => POINTER_SIZE,
+                        Some(wraps) => estimate_type_size(&wraps.type_name, types, sizes),
+                        None => 0,
+                    })
+                    .max()
+                    .unwrap_or_default()
+        }
+        RustTypeKind::Wrapper(inner) => estimate_type_size(&inner.type_name, types, sizes),
+        RustTypeKind::Unit(_) => 0,
+    };
+
+    sizes.insert(type_name.to_owned(), size);
+    size
+}
+
+/// Estimates the size of an `Option` of a type, which the niche of `OwnedPtr` and `NonZero`
+/// integers makes as large as the type itself.
+fn estimate_option_size(
+    type_name: &str,
+    types: &HashMap<String, RustTypeKind>,
+    sizes: &mut HashMap<String, usize>,
+) -> usize {
+    let size = estimate_type_size(type_name, types, sizes);
+    if type_name.starts_with("OwnedPtr<") || type_name.starts_with("core::num::NonZero") {
+        size
+    } else {
+        POINTER_SIZE + size
+    }
+}
+
+/// Checks whether `to` is held by value in `from`, directly or through other types.
+fn type_reaches(edges: &HashMap<String, Vec<String>>, from: &str, to: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![from];
+
+    while let Some(current) = pending.pop() {
+        if !visited.insert(current) {
+            continue;
+        }
+
+        for next in edges.get(current).into_iter().flatten() {
+            if next == to {
+                return true;
+            }
+            pending.push(next);
+        }
+    }
+
+    false
+}
+
+/// Gets the name of the type held by value in a field of type `type_name`, if any. Collections
+/// already hold their items on the heap.
+fn get_value_type_name(type_name: &str) -> Option<&str> {
+    let mut type_name = type_name;
+    while let Some(inner) = type_name
+        .strip_prefix("Option<")
+        .and_then(|value| value.strip_suffix('>'))
+    {
+        type_name = inner;
+    }
+
+    if type_name.contains(['<', ':', '[', '&']) {
+        None
+    } else {
+        Some(type_name)
+    }
+}
+
+/// Collects the names of schemas referenced by value, that is not through arrays, in `schema`.
+fn get_schema_value_refs(schema: &Schema, refs: &mut Vec<String>) {
+    match schema {
+        Schema::Ref(reference) => refs.push(reference.name().to_owned()),
+        Schema::OneOf(one_of) => {
+            for item in one_of.one_of.iter() {
+                get_schema_value_refs(item, refs);
+            }
+        }
+        Schema::AllOf(all_of) => {
+            for item in all_of.all_of.iter() {
+                get_schema_value_refs(item, refs);
+            }
+        }
+        Schema::Primitive(Primitive::Object(object)) => {
+            for item in object.properties.values() {
+                get_schema_value_refs(item, refs);
+            }
+        }
+        Schema::Primitive(_) | Schema::Constraint(_) => {}
+    }
+}
+
+fn schema_to_rust_type_kind(
+    specs: &Specification,
     entity: &Schema,
+    name: &str,
     allow_unknown_fields: bool,
     flatten_option: &FlattenOption,
     derives: Vec<String>,
+    context: &mut ResolutionContext,
 ) -> Result<Option<SchemaToRustTypeResult>> {
     Ok(match entity {
         Schema::Ref(reference) => {
@@ -1386,9 +4225,11 @@ fn schema_to_rust_type_kind(
                 schema_to_rust_type_kind(
                     specs,
                     ref_type,
+                    name,
                     allow_unknown_fields,
                     flatten_option,
                     derives,
+                    context,
                 )?
             } else {
                 Some(SchemaToRustTypeResult::Alias(RustAliasContent {
@@ -1433,6 +4274,7 @@ fn schema_to_rust_type_kind(
                 Ok(string_variants) if !string_variants.is_empty() => {
                     Some(SchemaToRustTypeResult::Type(RustTypeKind::Enum(RustEnum {
                         is_error: false,
+                        open: None,
                         variants: string_variants
                             .into_iter()
                             .flatten()
@@ -1443,6 +4285,7 @@ fn schema_to_rust_type_kind(
                                 error_text: None,
                                 error_code: None,
                                 wraps: None,
+                                boxed: false,
                             })
                             .collect(),
                         derives,
@@ -1451,15 +4294,39 @@ fn schema_to_rust_type_kind(
                 _ => None,
             }
         }
+        Schema::Primitive(Primitive::Object(object)) if object.map_value_schemas().is_some() => {
+            let map_type = get_rust_type_for_map(object, name, "value", context)?;
+
+            Some(SchemaToRustTypeResult::Type(RustTypeKind::Wrapper(
+                RustWrapper {
+                    type_name: map_type.type_name,
+                    serializer: map_type.serializer,
+                },
+            )))
+        }
         Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
             let mut fields = vec![];
-            get_schema_fields(entity, specs, &mut fields, flatten_option)?;
+            get_schema_fields(entity, specs, name, &mut fields, flatten_option, context)?;
+
+            let mut excluded_fields = vec![];
+            get_schema_excluded_fields(entity, specs, &mut excluded_fields, flatten_option)?;
+
+            if let Some(field) = fields.iter().find(|field| {
+                excluded_fields.contains(field.serde_rename.as_ref().unwrap_or(&field.name))
+            }) {
+                anyhow::bail!("Excluded field {} is also a property", field.name);
+            }
+
             Some(SchemaToRustTypeResult::Type(RustTypeKind::Struct(
                 RustStruct {
                     allow_unknown_fields,
-                    serde_as_obj: false,
+                    param_structure: None,
                     extra_ref_type: false,
+                    builder_defaults: None,
+                    borrowed_fields: None,
                     fields,
+                    excluded_fields,
+                    version_offsets: vec![],
                     derives,
                 },
             )))
@@ -1467,6 +4334,7 @@ fn schema_to_rust_type_kind(
         Schema::Primitive(Primitive::String(value)) => match &value.r#enum {
             Some(variants) => Some(SchemaToRustTypeResult::Type(RustTypeKind::Enum(RustEnum {
                 is_error: false,
+                open: None,
                 variants: variants
                     .iter()
                     .map(|item| RustVariant {
@@ -1476,6 +4344,7 @@ fn schema_to_rust_type_kind(
                         error_text: None,
                         error_code: None,
                         wraps: None,
+                        boxed: false,
                     })
                     .collect(),
                 derives,
@@ -1483,6 +4352,12 @@ fn schema_to_rust_type_kind(
             None => Some(SchemaToRustTypeResult::Type(RustTypeKind::Wrapper(
                 RustWrapper {
                     type_name: "String".into(),
+                    serializer: match (context.patterns.as_mut(), &value.pattern) {
+                        (Some(patterns), Some(pattern)) => {
+                            with_pattern_validation(None, &patterns.for_type(name, pattern))
+                        }
+                        _ => None,
+                    },
                 },
             ))),
         },
@@ -1580,7 +4455,9 @@ fn visit_schema_for_flatten_only(
             }
         }
         Schema::Primitive(Primitive::Object(object)) => {
-            for (_, prop_type) in object.properties.iter() {
+            let map_value_types = object.map_value_schemas().unwrap_or_default();
+
+            for prop_type in object.properties.values().chain(map_value_types) {
                 match prop_type {
                     Schema::Ref(reference) => {
                         non_flatten_fields.insert(reference.name().to_owned());
@@ -1612,8 +4489,10 @@ fn visit_schema_for_flatten_only(
 fn get_schema_fields(
     schema: &Schema,
     specs: &Specification,
+    name: &str,
     fields: &mut Vec<RustField>,
     flatten_option: &FlattenOption,
+    context: &mut ResolutionContext,
 ) -> Result<()> {
     match schema {
         Schema::Ref(value) => {
@@ -1624,7 +4503,7 @@ fn get_schema_fields(
             };
 
             // Schema redirection
-            get_schema_fields(ref_type, specs, fields, flatten_option)?;
+            get_schema_fields(ref_type, specs, name, fields, flatten_option, context)?;
         }
         Schema::AllOf(value) => {
             for item in value.all_of.iter() {
@@ -1637,239 +4516,2055 @@ fn get_schema_fields(
                             }
                         };
 
-                        if should_flatten {
-                            get_schema_fields(item, specs, fields, flatten_option)?;
-                        } else {
-                            let field_name = get_all_of_ref_name_override(reference.name())
-                                .unwrap_or_else(|| reference.name().to_lowercase());
+                        if should_flatten {
+                            get_schema_fields(item, specs, name, fields, flatten_option, context)?;
+                        } else {
+                            let field_name = get_all_of_ref_name_override(reference.name())
+                                .unwrap_or_else(|| reference.name().to_lowercase());
+
+                            fields.push(RustField {
+                                description: reference.description.to_owned(),
+                                name: field_name,
+                                optional: false,
+                                fixed: None,
+                                arc_wrap: false,
+                                auto_boxed: false,
+                                type_name: to_starknet_rs_name(reference.name()),
+                                serde_rename: None,
+                                serde_flatten: true,
+                                serializer: None,
+                            });
+                        }
+                    }
+                    _ => {
+                        // We don't have a choice but to flatten it
+                        get_schema_fields(item, specs, name, fields, flatten_option, context)?;
+                    }
+                }
+            }
+        }
+        Schema::Primitive(Primitive::Object(value)) => {
+            for (prop_name, prop_value) in value.properties.iter() {
+                // For fields we keep things simple and only use one line
+                let doc_string = match prop_value.description() {
+                    Some(text) => Some(text),
+                    None => match prop_value.title() {
+                        Some(text) => Some(text),
+                        None => prop_value.summary(),
+                    },
+                };
+
+                let field_optional = !value.required.contains(prop_name);
+
+                // Absent and `null` values are not told apart for optional fields
+                let prop_schema = match prop_value.non_null_option() {
+                    Some(option) if field_optional => option,
+                    _ => prop_value,
+                };
+
+                let field_type = get_rust_type_for_field(prop_schema, name, prop_name, context)?;
+
+                let field_name = to_rust_field_name(prop_name);
+                let rename = if prop_name == &field_name {
+                    None
+                } else {
+                    Some(prop_name.to_owned())
+                };
+
+                let fixed = if field_optional {
+                    None
+                } else {
+                    get_fixed_field_value(prop_schema).map(|value| FixedField {
+                        name: field_name.clone(),
+                        value,
+                        version_offsets: vec![],
+                        must_present_in_deser: false,
+                    })
+                };
+
+                // Optional field transformation
+                let serializer = if field_optional {
+                    field_type.serializer.map(|value| value.to_optional())
+                } else {
+                    field_type.serializer
+                };
+
+                fields.push(RustField {
+                    description: doc_string.map(|value| to_starknet_rs_doc(value, false)),
+                    name: field_name,
+                    optional: field_optional,
+                    fixed,
+                    arc_wrap: false,
+                    auto_boxed: false,
+                    type_name: field_type.type_name,
+                    serde_rename: rename,
+                    serde_flatten: false,
+                    serializer,
+                });
+            }
+        }
+        _ => {
+            dbg!(schema);
+            anyhow::bail!("Unexpected schema type when getting object fields");
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the value of a field that can only take a single value according to the specification,
+/// in the same notation as fixed fields from profiles. Named single-variant enums are kept as
+/// regular fields since their types are part of the public API.
+fn get_fixed_field_value(schema: &Schema) -> Option<String> {
+    if let Some(value) = schema.const_value() {
+        return Some(match value {
+            serde_json::Value::String(value) => format!("{value:?}"),
+            value => format!("&{value}"),
+        });
+    }
+
+    match schema {
+        Schema::Primitive(Primitive::String(StringPrimitive {
+            r#enum: Some(variants),
+            ..
+        })) if variants.len() == 1 => Some(format!("{:?}", variants[0])),
+        _ => None,
+    }
+}
+
+/// Collects fields that must not all be present, following flattened schemas the same way as
+/// `get_schema_fields`.
+fn get_schema_excluded_fields(
+    schema: &Schema,
+    specs: &Specification,
+    excluded_fields: &mut Vec<String>,
+    flatten_option: &FlattenOption,
+) -> Result<()> {
+    match schema {
+        Schema::Ref(value) => {
+            let ref_type_name = value.name();
+            let ref_type = match specs.components.schemas.get(ref_type_name) {
+                Some(ref_type) => ref_type,
+                None => anyhow::bail!("Ref target type not found: {}", ref_type_name),
+            };
+
+            get_schema_excluded_fields(ref_type, specs, excluded_fields, flatten_option)?;
+        }
+        Schema::AllOf(value) => {
+            for item in value.all_of.iter() {
+                let should_flatten = match (item, flatten_option) {
+                    (Schema::Ref(reference), FlattenOption::Selected(flatten_types)) => {
+                        flatten_types.contains(&reference.name().to_owned())
+                    }
+                    _ => true,
+                };
+
+                if should_flatten {
+                    get_schema_excluded_fields(item, specs, excluded_fields, flatten_option)?;
+                }
+            }
+        }
+        Schema::Primitive(Primitive::Object(value)) => {
+            excluded_fields.extend(value.excluded_fields().iter().cloned());
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Gets the type of field `field_name` in type `type_name`. Named types are synthesized for
+/// anonymous object, `oneOf` and `allOf` schemas.
+fn get_rust_type_for_field(
+    schema: &Schema,
+    type_name: &str,
+    field_name: &str,
+    context: &mut ResolutionContext,
+) -> Result<RustFieldType> {
+    match schema {
+        Schema::Ref(value) => {
+            let ref_type_name = value.name();
+
+            if let Some(mut type_override) = get_field_type_override(ref_type_name) {
+                // Types generated from the schema check patterns themselves, but overrides don't
+                if let Some(marker) = context
+                    .patterns
+                    .as_mut()
+                    .and_then(|patterns| patterns.for_schema(ref_type_name))
+                {
+                    type_override.serializer =
+                        with_pattern_validation(type_override.serializer, &marker);
+                }
+
+                // Hard-coded special rules
+                Ok(type_override)
+            } else {
+                // TODO: take non-alias refs into account
+                Ok(RustFieldType {
+                    type_name: to_starknet_rs_name(ref_type_name),
+                    serializer: None,
+                })
+            }
+        }
+        Schema::Primitive(Primitive::Object(object)) if object.map_value_schemas().is_some() => {
+            get_rust_type_for_map(object, type_name, field_name, context)
+        }
+        Schema::OneOf(_) if schema.non_null_option().is_some() => {
+            let option_type = get_rust_type_for_field(
+                schema.non_null_option().unwrap(),
+                type_name,
+                field_name,
+                context,
+            )?;
+
+            Ok(RustFieldType {
+                type_name: format!("Option<{}>", option_type.type_name),
+                serializer: option_type.serializer.map(|value| value.to_optional()),
+            })
+        }
+        Schema::Constraint(constraint) => match &constraint.const_value {
+            Some(serde_json::Value::String(_)) => Ok(RustFieldType {
+                type_name: String::from("String"),
+                serializer: None,
+            }),
+            Some(serde_json::Value::Number(value)) => Ok(RustFieldType {
+                type_name: String::from(if value.is_u64() { "u64" } else { "i64" }),
+                serializer: None,
+            }),
+            Some(serde_json::Value::Bool(_)) => Ok(RustFieldType {
+                type_name: String::from("bool"),
+                serializer: None,
+            }),
+            _ => anyhow::bail!("Only constant constraints can be used as fields"),
+        },
+        Schema::OneOf(_) | Schema::AllOf(_) | Schema::Primitive(Primitive::Object(_)) => {
+            Ok(RustFieldType {
+                type_name: context
+                    .inline_types
+                    .register(type_name, field_name, schema)?,
+                serializer: None,
+            })
+        }
+        Schema::Primitive(value) => match value {
+            Primitive::Array(value) => {
+                let item_type =
+                    get_rust_type_for_field(&value.items, type_name, field_name, context)?;
+                Ok(RustFieldType {
+                    type_name: format!("Vec<{}>", item_type.type_name),
+                    serializer: item_type.serializer.map(|value| value.to_array()),
+                })
+            }
+            Primitive::Boolean(_) => Ok(RustFieldType {
+                type_name: String::from("bool"),
+                serializer: None,
+            }),
+            Primitive::Integer(value) => get_rust_type_for_integer(value),
+            Primitive::Null(_) => {
+                anyhow::bail!("`null` can only be used in `oneOf` unions")
+            }
+            Primitive::Object(_) => unreachable!("handled above"),
+            Primitive::String(value) => {
+                // Hacky solution but it's the best we can do given the specs
+                let mut field_type = match &value.description {
+                    Some(desc) if desc.contains("base64") => RustFieldType {
+                        type_name: String::from("Vec<u8>"),
+                        serializer: Some(SerializerOverride::serde_with("base64")?),
+                    },
+                    _ => RustFieldType {
+                        type_name: String::from("String"),
+                        serializer: None,
+                    },
+                };
+
+                if let (Some(patterns), Some(pattern)) = (context.patterns.as_mut(), &value.pattern)
+                {
+                    let marker = patterns.for_field(type_name, field_name, pattern);
+                    field_type.serializer = with_pattern_validation(field_type.serializer, &marker);
+                }
+
+                Ok(field_type)
+            }
+        },
+    }
+}
+
+/// Gets the narrowest integer type that holds all values allowed by the schema. Bounds that the
+/// type itself can't enforce are checked at deserialization with the `Bounded` adapter.
+fn get_rust_type_for_integer(value: &IntegerPrimitive) -> Result<RustFieldType> {
+    let format_type = match &value.format {
+        Some(format) => Some(
+            INTEGER_TYPES
+                .iter()
+                .find(|item| item.format == format)
+                .ok_or_else(|| anyhow::anyhow!("Unknown integer format: {}", format))?,
+        ),
+        None => None,
+    };
+
+    // Integers in the specs are treated as non-negative unless stated otherwise
+    let mut lower = value
+        .lower_bound()
+        .unwrap_or_else(|| format_type.map_or(0, |item| item.min));
+    let mut upper = value.upper_bound();
+    let mut non_zero = false;
+
+    if let Some(excluded) = value.excluded_value() {
+        if excluded == lower {
+            lower += 1;
+        } else if Some(excluded) == upper {
+            upper = Some(excluded - 1);
+        } else if excluded == 0 {
+            non_zero = true;
+        } else {
+            anyhow::bail!("Excluding integer value {} is not supported", excluded);
+        }
+    }
+
+    let int_type = match format_type {
+        Some(format_type) => *format_type,
+        None => {
+            let upper = upper.unwrap_or(if lower >= 0 {
+                u64::MAX as i128
+            } else {
+                i64::MAX as i128
+            });
+
+            *INTEGER_TYPES
+                .iter()
+                .find(|item| item.min <= lower && upper <= item.max)
+                .ok_or_else(|| anyhow::anyhow!("No integer type for range {lower}..={upper}"))?
+        }
+    };
+
+    let upper = upper.unwrap_or(int_type.max);
+    if lower < int_type.min || upper > int_type.max {
+        anyhow::bail!(
+            "Integer range {}..={} exceeds format {}",
+            lower,
+            upper,
+            int_type.format
+        );
+    }
+
+    let non_zero = non_zero || (int_type.min == 0 && lower >= 1);
+    let type_min = if non_zero && int_type.min == 0 {
+        1
+    } else {
+        int_type.min
+    };
+
+    if lower == type_min && upper == int_type.max {
+        Ok(RustFieldType {
+            type_name: String::from(if non_zero {
+                int_type.non_zero_type_name
+            } else {
+                int_type.type_name
+            }),
+            serializer: None,
+        })
+    } else if non_zero && int_type.min < 0 {
+        anyhow::bail!("Bounded non-zero signed integers are not supported");
+    } else {
+        // Negative const generic arguments must be enclosed in braces
+        let bound = |value: i128| {
+            if value < 0 {
+                format!("{{ {value} }}")
+            } else {
+                value.to_string()
+            }
+        };
+
+        Ok(RustFieldType {
+            type_name: String::from(int_type.type_name),
+            serializer: Some(SerializerOverride::SerdeAs(format!(
+                "Bounded<{}, {}>",
+                bound(lower),
+                bound(upper)
+            ))),
+        })
+    }
+}
+
+/// Gets the map type for objects with `additionalProperties` or `patternProperties` schemas. Keys
+/// are typed with `propertyNames` when present.
+fn get_rust_type_for_map(
+    object: &ObjectPrimitive,
+    type_name: &str,
+    field_name: &str,
+    context: &mut ResolutionContext,
+) -> Result<RustFieldType> {
+    let value_schemas = object
+        .map_value_schemas()
+        .ok_or_else(|| anyhow::anyhow!("Object is not a map"))?;
+    if value_schemas
+        .iter()
+        .any(|schema| schema != &value_schemas[0])
+    {
+        anyhow::bail!("Maps with different value schemas are not supported");
+    }
+
+    let key_type = match &object.property_names {
+        Some(key_schema) => get_rust_type_for_field(key_schema, type_name, field_name, context)?,
+        None => RustFieldType {
+            type_name: String::from("String"),
+            serializer: None,
+        },
+    };
+    let value_type = get_rust_type_for_field(value_schemas[0], type_name, field_name, context)?;
+
+    let serializer = if key_type.serializer.is_some() || value_type.serializer.is_some() {
+        Some(SerializerOverride::SerdeAs(format!(
+            "IndexMap<{}, {}>",
+            key_type
+                .serializer
+                .as_ref()
+                .map_or("_", |serializer| serializer.as_serde_as()),
+            value_type
+                .serializer
+                .as_ref()
+                .map_or("_", |serializer| serializer.as_serde_as()),
+        )))
+    } else {
+        None
+    };
+
+    Ok(RustFieldType {
+        type_name: format!(
+            "IndexMap<{}, {}, RandomState>",
+            key_type.type_name, value_type.type_name
+        ),
+        serializer,
+    })
+}
+
+fn resolve_methods(
+    specs: &Specification,
+    options: &ProfileOptions,
+    sources: &IndexMap<String, SpecFile>,
+    method_results: &IndexMap<String, RustFieldType>,
+) -> Result<Vec<MethodMetadata>> {
+    specs
+        .methods
+        .iter()
+        .map(|method| {
+            let error_codes = method
+                .errors
+                .iter()
+                .flatten()
+                .map(
+                    |reference| match specs.components.errors.get(reference.name()) {
+                        Some(ErrorType::Error(error)) => Ok(error.code),
+                        Some(ErrorType::Reference(_)) => {
+                            anyhow::bail!("Error redirection not implemented")
+                        }
+                        None => anyhow::bail!("Error not found: {}", reference.name()),
+                    },
+                )
+                .collect::<Result<_>>()?;
+
+            Ok(MethodMetadata {
+                name: method.name.clone(),
+                variant_name: to_method_variant_name(&method.name),
+                summary: method.summary.clone(),
+                params: method
+                    .params
+                    .iter()
+                    .map(|param| (param.name.clone(), param.required))
+                    .collect(),
+                param_structure: get_param_structure(method, options),
+                result_type: method_results
+                    .get(&method.name)
+                    .map(|result_type| result_type.type_name.clone()),
+                error_codes,
+                source: match sources.get(&method.name) {
+                    Some(source) => *source,
+                    None => anyhow::bail!("Source file not found for method {}", method.name),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Gets the param structure of a method, which defaults to `either` as per OpenRPC.
+fn get_param_structure(method: &Method, options: &ProfileOptions) -> ParamStructure {
+    options
+        .param_structures
+        .find_param_structure(&method.name)
+        .or(method.param_structure)
+        .unwrap_or_default()
+}
+
+fn get_field_type_override(type_name: &str) -> Option<RustFieldType> {
+    Some(match type_name {
+        "ADDRESS" | "STORAGE_KEY" | "TXN_HASH" | "FELT" | "BLOCK_HASH" | "CHAIN_ID"
+        | "PROTOCOL_VERSION" => RustFieldType {
+            type_name: String::from("Felt"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("UfeHex"))),
+        },
+        "ETH_ADDRESS" => RustFieldType {
+            type_name: String::from("EthAddress"),
+            serializer: None,
+        },
+        "EXECUTION_RESULT" => RustFieldType {
+            type_name: String::from("ExecutionResult"),
+            serializer: None,
+        },
+        "BLOCK_NUMBER" => RustFieldType {
+            type_name: String::from("u64"),
+            serializer: None,
+        },
+        "NUM_AS_HEX" => RustFieldType {
+            type_name: String::from("u64"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
+        },
+        "SIGNATURE" => RustFieldType {
+            type_name: String::from("Vec<Felt>"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("Vec<UfeHex>"))),
+        },
+        "EVENT_KEYS" => RustFieldType {
+            type_name: String::from("Vec<Vec<Felt>>"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from(
+                "Vec<Vec<UfeHex>>",
+            ))),
+        },
+        "NODE_HASH_TO_NODE_MAPPING" => RustFieldType {
+            type_name: String::from("IndexMap<Felt, MerkleNode, RandomState>"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("MerkleNodeMap"))),
+        },
+        "CONTRACT_ABI" => RustFieldType {
+            type_name: String::from("Vec<LegacyContractAbiEntry>"),
+            serializer: None,
+        },
+        "CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
+            type_name: String::from("Vec<ContractEntryPoint>"),
+            serializer: None,
+        },
+        "LEGACY_CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
+            type_name: String::from("Vec<LegacyContractEntryPoint>"),
+            serializer: None,
+        },
+        "TXN_TYPE" => RustFieldType {
+            type_name: String::from("String"),
+            serializer: None,
+        },
+        "NESTED_CALL" => RustFieldType {
+            type_name: String::from("FunctionInvocation"),
+            serializer: None,
+        },
+        "HASH_256" | "L1_TXN_HASH" => RustFieldType {
+            type_name: String::from("Hash256"),
+            serializer: None,
+        },
+        "u64" => RustFieldType {
+            type_name: String::from("u64"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
+        },
+        "u128" => RustFieldType {
+            type_name: String::from("u128"),
+            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
+        },
+        "SUBSCRIPTION_BLOCK_ID" => RustFieldType {
+            type_name: String::from("ConfirmedBlockId"),
+            serializer: None,
+        },
+        "TXN_STATUS_RESULT" => RustFieldType {
+            type_name: String::from("TransactionStatus"),
+            serializer: None,
+        },
+        _ => return None,
+    })
+}
+
+fn get_all_of_ref_name_override(type_name: &str) -> Option<String> {
+    match type_name {
+        "TXN_RECEIPT" => Some("receipt".into()),
+        "RECEIPT_BLOCK" => Some("block".into()),
+        _ => None,
+    }
+}
+
+/// Whether a generated `serde_as` adapter is referenced by any serializer in the generated types.
+fn is_serde_as_adapter_used(types: &[&RustType], adapter: &str) -> bool {
+    let adapter_regex = Regex::new(&format!(r"\b{adapter}\b")).unwrap();
+
+    types
+        .iter()
+        .flat_map(|rust_type| rust_type.serializers())
+        .any(|serializer| match serializer {
+            SerializerOverride::Serde(_) => false,
+            SerializerOverride::SerdeAs(serde_as) => adapter_regex.is_match(serde_as),
+        })
+}
+
+/// Gets the type whose `JsonSchema` describes the JSON of `type_name` (de)serialized with the
+/// `serde_as` adapter `serde_as`.
+fn get_json_schema_type(serde_as: &str, type_name: &str) -> String {
+    if let Some(inner_type) = strip_generic(type_name, "Option") {
+        return format!(
+            "Option<{}>",
+            get_json_schema_type(
+                strip_generic(serde_as, "Option").unwrap_or(serde_as),
+                inner_type
+            )
+        );
+    }
+    if let Some(item_type) = strip_generic(type_name, "Vec") {
+        if let Some(item_as) = strip_generic(serde_as, "Vec").or_else(|| {
+            serde_as
+                .strip_prefix('[')
+                .and_then(|value| value.strip_suffix(']'))
+        }) {
+            return format!("Vec<{}>", get_json_schema_type(item_as, item_type));
+        }
+    }
+
+    match serde_as {
+        "UfeHex" | "NumAsHex" | "Base64" => String::from("String"),
+        _ if serde_as.starts_with("Validated<") => String::from("String"),
+        // Maps keyed by node hashes are (de)serialized as arrays of entries
+        "MerkleNodeMap" => {
+            match strip_generic(type_name, "IndexMap").and_then(|args| args.split(", ").nth(1)) {
+                Some(node_type) => format!("Vec<MerkleNodeMapEntry<{node_type}>>"),
+                None => panic!("unexpected MerkleNodeMap type {type_name}"),
+            }
+        }
+        // Adapters like `Bounded` or the ones for pointers keep the JSON type
+        _ => type_name.to_owned(),
+    }
+}
+
+/// Wraps the serializer of a field with pattern validation.
+fn with_pattern_validation(
+    serializer: Option<SerializerOverride>,
+    marker: &str,
+) -> Option<SerializerOverride> {
+    Some(SerializerOverride::SerdeAs(match serializer {
+        Some(serializer) => format!("Validated<{}, {}>", marker, serializer.as_serde_as()),
+        None => format!("Validated<{marker}>"),
+    }))
+}
+
+fn render_pattern_validation_stdout() {
+    println!("/// A string pattern from the specification.");
+    println!("#[cfg(feature = \"validate_patterns\")]");
+    println!("trait SpecPattern {{");
+    println!("    /// Location of the pattern in the specification.");
+    println!("    const RULE: &'static str;");
+    println!("    const PATTERN: &'static str;");
+    println!();
+    println!("    fn regex() -> &'static regex::Regex;");
+    println!("}}");
+    println!();
+    println!(
+        "/// Checks strings against the pattern `P` before deserializing them with `T`, when the"
+    );
+    println!("/// `validate_patterns` feature is enabled.");
+    println!("struct Validated<P, T = serde_with::Same>(core::marker::PhantomData<(P, T)>);");
+    println!();
+    println!("impl<P, T, U> serde_with::SerializeAs<U> for Validated<P, T>");
+    println!("where");
+    println!("    T: serde_with::SerializeAs<U>,");
+    println!("    U: ?Sized,");
+    println!("{{");
+    println!("    fn serialize_as<S: Serializer>(value: &U, serializer: S) -> Result<S::Ok, S::Error> {{");
+    println!("        T::serialize_as(value, serializer)");
+    println!("    }}");
+    println!("}}");
+    println!();
+    println!("#[cfg(feature = \"validate_patterns\")]");
+    println!("impl<'de, P, T, U> serde_with::DeserializeAs<'de, U> for Validated<P, T>");
+    println!("where");
+    println!("    P: SpecPattern,");
+    println!("    T: serde_with::DeserializeAs<'de, U>,");
+    println!("{{");
+    println!(
+        "    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<U, D::Error> {{"
+    );
+    println!("        use serde::de::IntoDeserializer;");
+    println!();
+    println!("        let value = String::deserialize(deserializer)?;");
+    println!("        if !P::regex().is_match(&value) {{");
+    println!("            return Err(serde::de::Error::custom(format!(");
+    println!("                \"value `{{}}` does not match pattern `{{}}` of {{}}\",");
+    println!("                value,");
+    println!("                P::PATTERN,");
+    println!("                P::RULE");
+    println!("            )));");
+    println!("        }}");
+    println!();
+    println!(
+        "        T::deserialize_as(IntoDeserializer::<'de, D::Error>::into_deserializer(value))"
+    );
+    println!("    }}");
+    println!("}}");
+    println!();
+    println!("#[cfg(not(feature = \"validate_patterns\"))]");
+    println!("impl<'de, P, T, U> serde_with::DeserializeAs<'de, U> for Validated<P, T>");
+    println!("where");
+    println!("    T: serde_with::DeserializeAs<'de, U>,");
+    println!("{{");
+    println!(
+        "    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<U, D::Error> {{"
+    );
+    println!("        T::deserialize_as(deserializer)");
+    println!("    }}");
+    println!("}}");
+}
+
+fn render_bounded_adapter_stdout() {
+    println!("/// Integer bounds from the specification, checked on deserialization.");
+    println!("struct Bounded<const MIN: i128, const MAX: i128>;");
+    println!();
+    println!("impl<T, const MIN: i128, const MAX: i128> serde_with::SerializeAs<T> for Bounded<MIN, MAX>");
+    println!("where");
+    println!("    T: Serialize,");
+    println!("{{");
+    println!("    fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {{");
+    println!("        value.serialize(serializer)");
+    println!("    }}");
+    println!("}}");
+    println!();
+    println!("impl<'de, T, const MIN: i128, const MAX: i128> serde_with::DeserializeAs<'de, T>");
+    println!("    for Bounded<MIN, MAX>");
+    println!("where");
+    println!("    T: Deserialize<'de> + Copy + Into<i128>,");
+    println!("{{");
+    println!(
+        "    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {{"
+    );
+    println!("        let value = T::deserialize(deserializer)?;");
+    println!("        if !(MIN..=MAX).contains(&value.into()) {{");
+    println!("            return Err(serde::de::Error::custom(format!(");
+    println!("                \"value out of range: expected {{MIN}}..={{MAX}}\"");
+    println!("            )));");
+    println!("        }}");
+    println!("        Ok(value)");
+    println!("    }}");
+    println!("}}");
+}
+
+fn render_method_table_stdout(methods: &[MethodMetadata]) {
+    println!("/// Specification file a method is defined in.");
+    println!("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]");
+    println!("pub enum MethodSource {{");
+    println!("    Main,");
+    println!("    Write,");
+    println!("    Trace,");
+    println!("    Ws,");
+    println!("}}");
+    println!();
+
+    println!("/// Parameter of a JSON-RPC method.");
+    println!("#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+    println!("pub struct MethodParamInfo {{");
+    println!("    pub name: &'static str,");
+    println!("    pub required: bool,");
+    println!("}}");
+    println!();
+
+    println!("/// Metadata of a JSON-RPC method from the specification.");
+    println!("#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+    println!("pub struct MethodInfo {{");
+    println!("    pub name: &'static str,");
+    println!("    pub summary: &'static str,");
+    println!("    pub params: &'static [MethodParamInfo],");
+    println!("    /// Rust type of the result, as decoded into `JsonRpcResponseData`");
+    println!("    pub result_type: Option<&'static str>,");
+    println!("    /// Codes of the errors the method can return");
+    println!("    pub error_codes: &'static [u32],");
+    println!("    pub source: MethodSource,");
+    println!("}}");
+    println!();
+
+    println!("/// Metadata of all methods in the specification.");
+    println!("pub const METHODS: &[MethodInfo] = &[");
+    for method in methods.iter() {
+        println!("    MethodInfo {{");
+        println!("        name: {:?},", method.name);
+        println!("        summary: {:?},", method.summary);
+        if method.params.is_empty() {
+            println!("        params: &[],");
+        } else {
+            println!("        params: &[");
+            for (name, required) in method.params.iter() {
+                println!("            MethodParamInfo {{");
+                println!("                name: {name:?},");
+                println!("                required: {required},");
+                println!("            }},");
+            }
+            println!("        ],");
+        }
+        match &method.result_type {
+            Some(result_type) => println!("        result_type: Some({result_type:?}),"),
+            None => println!("        result_type: None,"),
+        }
+        println!(
+            "        error_codes: &[{}],",
+            method
+                .error_codes
+                .iter()
+                .map(|code| code.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        println!("        source: MethodSource::{:?},", method.source);
+        println!("    }},");
+    }
+    println!("];");
+}
+
+fn render_request_data_stdout(methods: &[MethodMetadata]) {
+    println!("/// Request of any method, identified by the `method` field of JSON-RPC requests.");
+    println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+    println!("pub enum JsonRpcRequestData {{");
+    for method in methods.iter() {
+        println!("    {0}({0}Request),", method.variant_name);
+    }
+    println!("}}");
+    println!();
+
+    let by_name_methods = methods
+        .iter()
+        .filter(|method| method.param_structure == ParamStructure::ByName)
+        .map(|method| format!("Method::{}", method.variant_name))
+        .collect::<Vec<_>>();
+
+    println!("impl JsonRpcRequestData {{");
+    println!("    pub const fn method(&self) -> Method {{");
+    println!("        match self {{");
+    for method in methods.iter() {
+        print_match_arm(
+            &format!("Self::{}(_)", method.variant_name),
+            &format!("Method::{}", method.variant_name),
+        );
+    }
+    println!("        }}");
+    println!("    }}");
+    println!();
+    println!(
+        "    /// Deserializes the params of a request for `method` straight from their raw JSON."
+    );
+    println!("    fn from_raw_params<E: serde::de::Error>(");
+    println!("        method: Method,");
+    println!("        params: Option<&serde_json::value::RawValue>,");
+    println!("    ) -> Result<Self, E> {{");
+    println!("        fn parse<'a, T, E>(params: &'a str) -> Result<T, E>");
+    println!("        where");
+    println!("            T: Deserialize<'a>,");
+    println!("            E: serde::de::Error,");
+    println!("        {{");
+    println!("            serde_json::from_str(params).map_err(E::custom)");
+    println!("        }}");
+    println!();
+    if by_name_methods.is_empty() {
+        println!("        // Omitted params are equivalent to an empty list");
+        println!("        let params = params.map_or(\"[]\", |params| params.get());");
+    } else {
+        println!(
+            "        // Omitted params are equivalent to an empty list, or an empty object for"
+        );
+        println!("        // methods taking params by name");
+        println!("        let params = match params {{");
+        println!("            Some(params) => params.get(),");
+        println!("            None => match method {{");
+        let arm = format!(
+            "                {} => \"{{}}\",",
+            by_name_methods.join(" | ")
+        );
+        if arm.len() <= MAX_LINE_LENGTH {
+            println!("{arm}");
+        } else {
+            for (ind, method) in by_name_methods.iter().enumerate() {
+                if ind == 0 {
+                    println!("                {method}");
+                } else if ind == by_name_methods.len() - 1 {
+                    println!("                | {method} => \"{{}}\",");
+                } else {
+                    println!("                | {method}");
+                }
+            }
+        }
+        println!("                _ => \"[]\",");
+        println!("            }},");
+        println!("        }};");
+    }
+    println!();
+    println!("        Ok(match method {{");
+    for method in methods.iter() {
+        print_match_arm(
+            &format!("Method::{}", method.variant_name),
+            &format!("Self::{}(parse(params)?)", method.variant_name),
+        );
+    }
+    println!("        }})");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("impl Serialize for JsonRpcRequestData {{");
+    println!("    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{");
+    println!("        use serde::ser::SerializeMap;");
+    println!();
+    println!("        let mut map = serializer.serialize_map(Some(2))?;");
+    println!("        map.serialize_entry(\"method\", &self.method())?;");
+    println!("        match self {{");
+    for method in methods.iter() {
+        print_match_arm(
+            &format!("Self::{}(params)", method.variant_name),
+            "map.serialize_entry(\"params\", params)?",
+        );
+    }
+    println!("        }}");
+    println!("        map.end()");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("impl<'de> Deserialize<'de> for JsonRpcRequestData {{");
+    println!(
+        "    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{"
+    );
+    println!("        #[derive(Deserialize)]");
+    println!("        struct Raw {{");
+    println!("            method: Method,");
+    println!("            #[serde(default)]");
+    println!("            params: Option<alloc::boxed::Box<serde_json::value::RawValue>>,");
+    println!("        }}");
+    println!();
+    println!("        let raw = Raw::deserialize(deserializer)?;");
+    println!();
+    println!("        Self::from_raw_params(raw.method, raw.params.as_deref())");
+    println!("    }}");
+    println!("}}");
+}
+
+fn render_response_data_stdout(
+    methods: &[MethodMetadata],
+    method_results: &IndexMap<String, RustFieldType>,
+) {
+    println!(
+        "/// Result of any method that has one, typed according to the method of the request."
+    );
+    println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+    println!("pub enum JsonRpcResponseData {{");
+    for method in methods.iter() {
+        if let Some(result_type) = method_results.get(&method.name) {
+            println!("    {}({}),", method.variant_name, result_type.type_name);
+        }
+    }
+    println!("}}");
+    println!();
+
+    let no_result_methods = methods
+        .iter()
+        .filter(|method| !method_results.contains_key(&method.name))
+        .map(|method| format!("Method::{}", method.variant_name))
+        .collect::<Vec<_>>();
+
+    println!("impl JsonRpcResponseData {{");
+    println!("    pub const fn method(&self) -> Method {{");
+    println!("        match self {{");
+    for method in methods.iter() {
+        if method_results.contains_key(&method.name) {
+            print_match_arm(
+                &format!("Self::{}(_)", method.variant_name),
+                &format!("Method::{}", method.variant_name),
+            );
+        }
+    }
+    println!("        }}");
+    println!("    }}");
+    println!();
+    println!("    /// Decodes the result of a request for `method`.");
+    println!(
+        "    pub fn decode(method: Method, result: serde_json::Value) -> Result<Self, serde_json::Error> {{"
+    );
+    println!("        Ok(match method {{");
+    for method in methods.iter() {
+        if let Some(result_type) = method_results.get(&method.name) {
+            let decoding_expr = result_type.data_decoding_expr("result");
+            let expr = format!("Self::{}({decoding_expr}?)", method.variant_name);
+            if expr.len() + 16 <= MAX_LINE_LENGTH {
+                print_match_arm(&format!("Method::{}", method.variant_name), &expr);
+            } else {
+                // Decoding first keeps rustfmt from breaking the decoding call itself
+                println!("            Method::{} => {{", method.variant_name);
+                let binding = format!("                let result = {decoding_expr}?;");
+                if binding.len() <= MAX_LINE_LENGTH {
+                    println!("{binding}");
+                } else {
+                    println!("                let result =");
+                    println!("                    {decoding_expr}?;");
+                }
+                println!("                Self::{}(result)", method.variant_name);
+                println!("            }}");
+            }
+        }
+    }
+    if !no_result_methods.is_empty() {
+        // Notifications never get responses
+        let pattern = no_result_methods.join(" | ");
+        if pattern.len() + 12 <= MAX_LINE_LENGTH {
+            println!("            {pattern} => {{");
+        } else {
+            for (ind, method) in no_result_methods.iter().enumerate() {
+                if ind == 0 {
+                    println!("            {method}");
+                } else if ind == no_result_methods.len() - 1 {
+                    println!("            | {method} => {{");
+                } else {
+                    println!("            | {method}");
+                }
+            }
+        }
+        println!("                return Err(serde::de::Error::custom(format!(");
+        println!("                    \"method `{{method}}` has no result\"");
+        println!("                )));");
+        println!("            }}");
+    }
+    println!("        }})");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("impl Serialize for JsonRpcResponseData {{");
+    println!("    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{");
+    println!("        match self {{");
+    for method in methods.iter() {
+        if let Some(result_type) = method_results.get(&method.name) {
+            print_match_arm(
+                &format!("Self::{}(result)", method.variant_name),
+                &result_type.serializing_expr("result", "serializer"),
+            );
+        }
+    }
+    println!("        }}");
+    println!("    }}");
+    println!("}}");
+}
+
+fn render_json_rpc_envelope_stdout() {
+    println!("/// Version of the JSON-RPC protocol, which is always `2.0`.");
+    println!("#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]");
+    println!("pub enum JsonRpcVersion {{");
+    println!("    #[default]");
+    println!("    #[serde(rename = \"2.0\")]");
+    println!("    V2,");
+    println!("}}");
+    println!();
+
+    println!("/// Identifier correlating a JSON-RPC response with its request.");
+    println!("#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]");
+    println!("#[serde(untagged)]");
+    println!("pub enum JsonRpcId {{");
+    println!("    Number(u64),");
+    println!("    String(String),");
+    println!("}}");
+    println!();
+
+    println!("/// JSON-RPC request, or notification if it has no `id`.");
+    println!("#[derive(Debug, Clone, PartialEq, Eq, Serialize)]");
+    println!("pub struct JsonRpcRequest {{");
+    println!("    pub jsonrpc: JsonRpcVersion,");
+    println!("    #[serde(default, skip_serializing_if = \"Option::is_none\")]");
+    println!("    pub id: Option<JsonRpcId>,");
+    println!("    #[serde(flatten)]");
+    println!("    pub data: JsonRpcRequestData,");
+    println!("}}");
+    println!();
+
+    println!("impl JsonRpcRequest {{");
+    println!("    pub fn new(id: JsonRpcId, data: JsonRpcRequestData) -> Self {{");
+    println!("        Self {{");
+    println!("            jsonrpc: JsonRpcVersion::V2,");
+    println!("            id: Some(id),");
+    println!("            data,");
+    println!("        }}");
+    println!("    }}");
+    println!();
+    println!("    pub fn notification(data: JsonRpcRequestData) -> Self {{");
+    println!("        Self {{");
+    println!("            jsonrpc: JsonRpcVersion::V2,");
+    println!("            id: None,");
+    println!("            data,");
+    println!("        }}");
+    println!("    }}");
+    println!();
+    println!("    pub const fn is_notification(&self) -> bool {{");
+    println!("        self.id.is_none()");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    // Flattening `data` would buffer the params, which `RawValue` can't be deserialized from
+    println!("impl<'de> Deserialize<'de> for JsonRpcRequest {{");
+    println!(
+        "    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{"
+    );
+    println!("        #[derive(Deserialize)]");
+    println!("        struct Raw {{");
+    println!("            jsonrpc: JsonRpcVersion,");
+    println!("            #[serde(default)]");
+    println!("            id: Option<JsonRpcId>,");
+    println!("            method: Method,");
+    println!("            #[serde(default)]");
+    println!("            params: Option<alloc::boxed::Box<serde_json::value::RawValue>>,");
+    println!("        }}");
+    println!();
+    println!("        let raw = Raw::deserialize(deserializer)?;");
+    println!();
+    println!("        Ok(Self {{");
+    println!("            jsonrpc: raw.jsonrpc,");
+    println!("            id: raw.id,");
+    println!("            data: JsonRpcRequestData::from_raw_params(raw.method, raw.params.as_deref())?,");
+    println!("        }})");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!(
+        "/// JSON-RPC response, with the result to be decoded against the method of the request."
+    );
+    println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+    println!("pub struct JsonRpcResponse {{");
+    println!("    pub jsonrpc: JsonRpcVersion,");
+    println!("    /// `None` if the `id` of the request could not be determined");
+    println!("    pub id: Option<JsonRpcId>,");
+    println!("    pub result: Result<serde_json::Value, JsonRpcError>,");
+    println!("}}");
+    println!();
+
+    println!("impl JsonRpcResponse {{");
+    println!(
+        "    /// Decodes the response to a request for `method`, converting errors defined in the"
+    );
+    println!("    /// specification to `StarknetError`.");
+    println!(
+        "    pub fn decode(self, method: Method) -> Result<JsonRpcResponseData, JsonRpcResponseError> {{"
+    );
+    println!("        match self.result {{");
+    println!("            Ok(result) => JsonRpcResponseData::decode(method, result)");
+    println!("                .map_err(JsonRpcResponseError::InvalidResult),");
+    println!("            Err(error) => Err(match StarknetError::try_from(error) {{");
+    println!("                Ok(error) => JsonRpcResponseError::Starknet(error),");
+    println!("                Err(error) => JsonRpcResponseError::Other(error),");
+    println!("            }}),");
+    println!("        }}");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("impl Serialize for JsonRpcResponse {{");
+    println!("    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{");
+    println!("        use serde::ser::SerializeMap;");
+    println!();
+    println!("        let mut map = serializer.serialize_map(Some(3))?;");
+    println!("        map.serialize_entry(\"jsonrpc\", &self.jsonrpc)?;");
+    println!("        map.serialize_entry(\"id\", &self.id)?;");
+    println!("        match &self.result {{");
+    println!("            Ok(result) => map.serialize_entry(\"result\", result)?,");
+    println!("            Err(error) => map.serialize_entry(\"error\", error)?,");
+    println!("        }}");
+    println!("        map.end()");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("impl<'de> Deserialize<'de> for JsonRpcResponse {{");
+    println!(
+        "    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{"
+    );
+    println!("        #[derive(Deserialize)]");
+    println!("        struct Raw {{");
+    println!("            jsonrpc: JsonRpcVersion,");
+    println!("            #[serde(default)]");
+    println!("            id: Option<JsonRpcId>,");
+    println!("            #[serde(default, deserialize_with = \"present\")]");
+    println!("            result: Option<serde_json::Value>,");
+    println!("            #[serde(default)]");
+    println!("            error: Option<JsonRpcError>,");
+    println!("        }}");
+    println!();
+    println!("        // Tells a `null` result apart from a missing one");
+    println!("        fn present<'de, D: Deserializer<'de>>(");
+    println!("            deserializer: D,");
+    println!("        ) -> Result<Option<serde_json::Value>, D::Error> {{");
+    println!("            serde_json::Value::deserialize(deserializer).map(Some)");
+    println!("        }}");
+    println!();
+    println!("        let raw = Raw::deserialize(deserializer)?;");
+    println!();
+    println!("        let result = match (raw.result, raw.error) {{");
+    println!("            (Some(result), None) => Ok(result),");
+    println!("            (None, Some(error)) => Err(error),");
+    println!("            _ => {{");
+    println!("                return Err(serde::de::Error::custom(");
+    println!("                    \"exactly one of `result` and `error` must be present\",");
+    println!("                ))");
+    println!("            }}");
+    println!("        }};");
+    println!();
+    println!("        Ok(Self {{");
+    println!("            jsonrpc: raw.jsonrpc,");
+    println!("            id: raw.id,");
+    println!("            result,");
+    println!("        }})");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("/// Error decoding a JSON-RPC response.");
+    println!("#[derive(Debug)]");
+    println!("pub enum JsonRpcResponseError {{");
+    println!("    /// Error defined in the specification");
+    println!("    Starknet(StarknetError),");
+    println!("    /// Error not defined in the specification, or with malformed data");
+    println!("    Other(JsonRpcError),");
+    println!("    /// Result not matching the result type of the method");
+    println!("    InvalidResult(serde_json::Error),");
+    println!("}}");
+    println!();
+    println!("#[cfg(feature = \"std\")]");
+    println!("impl std::error::Error for JsonRpcResponseError {{}}");
+    println!();
+    println!("impl core::fmt::Display for JsonRpcResponseError {{");
+    println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+    println!("        match self {{");
+    println!("            Self::Starknet(error) => write!(f, \"{{error}}\"),");
+    println!("            Self::Other(error) => {{");
+    println!("                write!(f, \"JSON-RPC error {{}}: {{}}\", error.code, error.message)");
+    println!("            }}");
+    println!("            Self::InvalidResult(error) => write!(f, \"invalid result: {{error}}\"),");
+    println!("        }}");
+    println!("    }}");
+    println!("}}");
+    println!();
+
+    println!("/// Single JSON-RPC message or a batch of them.");
+    println!("#[derive(Debug, Clone, PartialEq, Eq, Serialize)]");
+    println!("#[serde(untagged)]");
+    println!("pub enum JsonRpcPayload<T> {{");
+    println!("    Single(T),");
+    println!("    Batch(Vec<T>),");
+    println!("}}");
+    println!();
+    println!("impl<'de, T: Deserialize<'de>> Deserialize<'de> for JsonRpcPayload<T> {{");
+    println!(
+        "    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{"
+    );
+    // Deriving it as untagged would buffer messages, which `RawValue` params can't be
+    // deserialized from
+    println!("        struct PayloadVisitor<T>(core::marker::PhantomData<T>);");
+    println!();
+    println!(
+        "        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for PayloadVisitor<T> {{"
+    );
+    println!("            type Value = JsonRpcPayload<T>;");
+    println!();
+    println!("            fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+    println!("                formatter.write_str(\"a JSON-RPC message or a batch of them\")");
+    println!("            }}");
+    println!();
+    println!("            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>");
+    println!("            where");
+    println!("                A: serde::de::MapAccess<'de>,");
+    println!("            {{");
+    println!(
+        "                let deserializer = serde::de::value::MapAccessDeserializer::new(map);"
+    );
+    println!("                T::deserialize(deserializer).map(JsonRpcPayload::Single)");
+    println!("            }}");
+    println!();
+    println!("            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>");
+    println!("            where");
+    println!("                A: serde::de::SeqAccess<'de>,");
+    println!("            {{");
+    println!("                let mut batch = Vec::new();");
+    println!("                while let Some(message) = seq.next_element()? {{");
+    println!("                    batch.push(message);");
+    println!("                }}");
+    println!("                Ok(JsonRpcPayload::Batch(batch))");
+    println!("            }}");
+    println!("        }}");
+    println!();
+    println!("        deserializer.deserialize_any(PayloadVisitor(core::marker::PhantomData))");
+    println!("    }}");
+    println!("}}");
+}
+
+/// Prints a visitor deserializing method params directly from sequences and maps, as allowed by
+/// the param structure, along with the call driving it. Field values are read through the
+/// `Field{index}` wrappers.
+fn render_params_visitor_stdout(name: &str, param_structure: ParamStructure, fields: &[RustField]) {
+    if !fields.is_empty() && param_structure != ParamStructure::ByPosition {
+        println!("        #[derive(Deserialize)]");
+        println!("        #[serde(field_identifier)]");
+        println!("        enum Key {{");
+        for (ind_field, field) in fields.iter().enumerate() {
+            println!("            #[serde(rename = \"{}\")]", field.name);
+            println!("            Field{ind_field},");
+        }
+        println!("            #[serde(other)]");
+        println!("            Unknown,");
+        println!("        }}");
+        println!();
+    }
+
+    println!("        struct Visitor;");
+    println!();
+    println!("        impl<'de> serde::de::Visitor<'de> for Visitor {{");
+    println!("            type Value = {name};");
+    println!();
+    println!("            fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+    println!(
+        "                formatter.write_str(\"{}\")",
+        match param_structure {
+            ParamStructure::ByName => "an object of params",
+            ParamStructure::ByPosition => "an array of params",
+            ParamStructure::Either => "an array or an object of params",
+        }
+    );
+    println!("            }}");
+
+    if param_structure != ParamStructure::ByName {
+        println!();
+        println!("            fn visit_seq<A: serde::de::SeqAccess<'de>>(");
+        println!("                self,");
+        println!("                mut seq: A,");
+        println!("            ) -> Result<Self::Value, A::Error> {{");
+
+        for (ind_field, field) in fields.iter().enumerate() {
+            if field.optional {
+                // Omitted params can also be `null` to keep later ones in place
+                println!(
+                    "                let field{ind_field} = seq.next_element::<Option<Field{ind_field}>>()?.flatten();"
+                );
+            } else {
+                println!("                let field{ind_field} = seq");
+                println!("                    .next_element::<Field{ind_field}>()?");
+                println!(
+                    "                    .ok_or_else(|| serde::de::Error::invalid_length({ind_field}, &self))?;"
+                );
+            }
+        }
+        if !fields.is_empty() {
+            println!();
+        }
+
+        println!("                if seq.next_element::<serde::de::IgnoredAny>()?.is_some() {{");
+        print_error_return(
+            20,
+            "invalid_length",
+            &format!("{}, &self", fields.len() + 1),
+        );
+        println!("                }}");
+        println!();
+        render_params_construction_stdout(name, fields);
+        println!("            }}");
+    }
+
+    if param_structure != ParamStructure::ByPosition {
+        println!();
+        println!("            fn visit_map<A: serde::de::MapAccess<'de>>(");
+        println!("                self,");
+        println!("                mut map: A,");
+        println!("            ) -> Result<Self::Value, A::Error> {{");
+
+        if fields.is_empty() {
+            println!("                if map.next_key::<serde::de::IgnoredAny>()?.is_some() {{");
+            print_error_return(20, "invalid_length", "1, &self");
+            println!("                }}");
+            println!();
+        } else {
+            for ind_field in 0..fields.len() {
+                println!("                let mut field{ind_field} = None;");
+            }
+            println!();
+
+            println!("                while let Some(key) = map.next_key::<Key>()? {{");
+            println!("                    match key {{");
+            for (ind_field, field) in fields.iter().enumerate() {
+                println!("                        Key::Field{ind_field} => {{");
+                println!("                            if field{ind_field}.is_some() {{");
+                print_error_return(32, "duplicate_field", &format!("\"{}\"", field.name));
+                println!("                            }}");
+                if field.optional {
+                    println!(
+                        "                            field{ind_field} = Some(map.next_value::<Option<Field{ind_field}>>()?);"
+                    );
+                } else {
+                    println!(
+                        "                            field{ind_field} = Some(map.next_value::<Field{ind_field}>()?);"
+                    );
+                }
+                println!("                        }}");
+            }
+            println!("                        Key::Unknown => {{");
+            println!("                            map.next_value::<serde::de::IgnoredAny>()?;");
+            println!("                        }}");
+            println!("                    }}");
+            println!("                }}");
+            println!();
+
+            for (ind_field, field) in fields.iter().enumerate() {
+                if field.optional {
+                    println!("                let field{ind_field} = field{ind_field}.flatten();");
+                } else {
+                    println!(
+                        "                let Some(field{ind_field}) = field{ind_field} else {{"
+                    );
+                    print_error_return(20, "missing_field", &format!("\"{}\"", field.name));
+                    println!("                }};");
+                }
+            }
+            println!();
+        }
+
+        render_params_construction_stdout(name, fields);
+        println!("            }}");
+    }
+
+    println!("        }}");
+    println!();
+    println!(
+        "        deserializer.{}(Visitor)",
+        match param_structure {
+            ParamStructure::ByName => "deserialize_map",
+            ParamStructure::ByPosition => "deserialize_seq",
+            ParamStructure::Either => "deserialize_any",
+        }
+    );
+}
+
+/// Prints the construction of params from the `field{index}` variables read by visitors.
+fn render_params_construction_stdout(name: &str, fields: &[RustField]) {
+    if fields.is_empty() {
+        println!("                Ok({name})");
+        return;
+    }
+
+    println!("                Ok({name} {{");
+    for (ind_field, field) in fields.iter().enumerate() {
+        if field.optional {
+            println!(
+                "                    {}: field{}.map(|f| f.value),",
+                field.name, ind_field
+            );
+        } else {
+            println!(
+                "                    {}: field{}.value,",
+                field.name, ind_field
+            );
+        }
+    }
+    println!("                }})");
+}
+
+/// Prints a statement returning a `serde::de::Error` built with `constructor`, breaking the
+/// arguments into their own line when too long.
+fn print_error_return(indent_spaces: usize, constructor: &str, args: &str) {
+    let indent = " ".repeat(indent_spaces);
+    let error = format!("serde::de::Error::{constructor}({args})");
+    let line = format!("{indent}return Err({error});");
+    // rustfmt breaks returned calls one column before the limit, and calls whose arguments are
+    // wider than `fn_call_width` even when they fit
+    if line.len() < MAX_LINE_LENGTH && error.len() <= FN_CALL_WIDTH {
+        println!("{line}");
+    } else {
+        println!("{indent}return Err(serde::de::Error::{constructor}(");
+        println!("{indent}    {args},");
+        println!("{indent}));");
+    }
+}
+
+/// Prints a match arm at the indentation of generated functions, moving the expression into a
+/// block when the line gets too long, or breaking the argument of a call if even that's too long.
+fn print_match_arm(pattern: &str, expr: &str) {
+    let arm = format!("            {pattern} => {expr},");
+    let block = format!("                {expr}");
+    // rustfmt already moves arms ending with `?` into a block one column before the limit
+    let arm_limit = if expr.ends_with('?') {
+        MAX_LINE_LENGTH - 2
+    } else {
+        MAX_LINE_LENGTH
+    };
+    if arm.len() <= arm_limit {
+        println!("{arm}");
+    } else if block.len() <= MAX_LINE_LENGTH {
+        println!("            {pattern} => {{");
+        println!("{block}");
+        println!("            }}");
+    } else {
+        let (callee, arg) = expr
+            .strip_suffix(')')
+            .and_then(|expr| expr.split_once('('))
+            .expect("only calls can be broken");
+        println!("            {pattern} => {callee}(");
+        println!("                {arg},");
+        println!("            ),");
+    }
+}
+
+/// Prints the signature of `JsonSchema::json_schema`, naming the `SchemaGenerator` only when the
+/// schema references the subschemas it collects.
+fn print_json_schema_fn_signature(uses_generator: bool) {
+    println!(
+        "    fn json_schema({}: &mut schemars::SchemaGenerator) -> schemars::Schema {{",
+        if uses_generator { "generator" } else { "_" }
+    );
+}
+
+/// Prints the JSON lines of a schema, indented as the returned expression of `json_schema`.
+fn print_json_schema_macro_stdout(lines: &[String]) {
+    for (ind, line) in lines.iter().enumerate() {
+        if ind == 0 {
+            println!("        schemars::json_schema!({line}");
+        } else if ind == lines.len() - 1 {
+            println!("        {line})");
+        } else {
+            println!("        {line}");
+        }
+    }
+}
+
+/// Pushes a JSON array entry of an object nested one level deep, one item per line when too long.
+fn push_json_array_lines<T: AsRef<str>>(lines: &mut Vec<String>, key: &str, items: &[T]) {
+    let items = items.iter().map(|item| item.as_ref()).collect::<Vec<_>>();
+    let line = format!("    \"{}\": [{}],", key, items.join(", "));
+    if line.len() + 8 <= MAX_LINE_LENGTH {
+        lines.push(line);
+    } else {
+        lines.push(format!("    \"{key}\": ["));
+        for item in items.iter() {
+            lines.push(format!("        {item},"));
+        }
+        lines.push(String::from("    ],"));
+    }
+}
+
+fn print_arbitrary_fn_signature(uses_input: bool) {
+    println!(
+        "    fn arbitrary({}: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {{",
+        if uses_input { "u" } else { "_" }
+    );
+}
+
+/// Prints a field initializer inside `Ok(Self {`, moving the value to its own line when too long.
+fn print_struct_field_init(name: &str, expr: &str) {
+    let line = format!("            {name}: {expr},");
+    if line.len() <= MAX_LINE_LENGTH {
+        println!("{line}");
+    } else {
+        println!("            {name}:");
+        println!("                {expr},");
+    }
+}
+
+/// Renders `ArbitraryAs`, the `Arbitrary` counterpart of `serde_as` adapters, for adapters used in
+/// generated types. Values of pattern-validated fields not matching their patterns are rejected.
+fn render_arbitrary_as_stdout(types: &[&RustType], has_patterns: bool) {
+    println!("/// Generates values of `T` that serialize to valid JSON through the implementing adapter.");
+    println!("#[cfg(feature = \"arbitrary\")]");
+    println!("trait ArbitraryAs<'a, T> {{");
+    println!("    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<T>;");
+    println!("}}");
+
+    // Adapters for which all values of the type are valid
+    let mut plain_adapters = vec!["serde_with::Same", "UfeHex", "NumAsHex", "MerkleNodeMap"];
+    plain_adapters.extend(
+        SERDE_WITH_ADAPTERS
+            .iter()
+            .filter(|adapter| adapter.is_used(types))
+            .map(|adapter| adapter.adapter),
+    );
+    for adapter in plain_adapters {
+        println!();
+        println!("#[cfg(feature = \"arbitrary\")]");
+        println!("impl<'a, T: arbitrary::Arbitrary<'a>> ArbitraryAs<'a, T> for {adapter} {{");
+        println!(
+            "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<T> {{"
+        );
+        println!("        u.arbitrary()");
+        println!("    }}");
+        println!("}}");
+    }
+
+    println!();
+    println!("#[cfg(feature = \"arbitrary\")]");
+    println!("impl<'a, T, U: ArbitraryAs<'a, T>> ArbitraryAs<'a, Option<T>> for Option<U> {{");
+    println!(
+        "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Option<T>> {{"
+    );
+    println!("        Ok(if u.arbitrary()? {{");
+    println!("            Some(U::arbitrary_as(u)?)");
+    println!("        }} else {{");
+    println!("            None");
+    println!("        }})");
+    println!("    }}");
+    println!("}}");
+    println!();
+    println!("#[cfg(feature = \"arbitrary\")]");
+    println!("impl<'a, T, U: ArbitraryAs<'a, T>> ArbitraryAs<'a, Vec<T>> for Vec<U> {{");
+    println!(
+        "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Vec<T>> {{"
+    );
+    println!("        let mut items = Vec::new();");
+    println!("        while u.arbitrary()? {{");
+    println!("            items.push(U::arbitrary_as(u)?);");
+    println!("        }}");
+    println!("        Ok(items)");
+    println!("    }}");
+    println!("}}");
+
+    if is_serde_as_adapter_used(types, "Bounded") {
+        println!();
+        println!("#[cfg(feature = \"arbitrary\")]");
+        println!("impl<'a, T, const MIN: i128, const MAX: i128> ArbitraryAs<'a, T> for Bounded<MIN, MAX>");
+        println!("where");
+        println!("    T: TryFrom<i128>,");
+        println!("{{");
+        println!(
+            "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<T> {{"
+        );
+        println!("        T::try_from(u.int_in_range(MIN..=MAX)?)");
+        println!("            .map_err(|_| arbitrary::Error::IncorrectFormat)");
+        println!("    }}");
+        println!("}}");
+    }
+
+    if has_patterns {
+        println!();
+        println!("#[cfg(all(feature = \"arbitrary\", feature = \"validate_patterns\"))]");
+        println!("impl<'a, P, T, U> ArbitraryAs<'a, U> for Validated<P, T>");
+        println!("where");
+        println!("    P: SpecPattern,");
+        println!("    T: ArbitraryAs<'a, U> + serde_with::SerializeAs<U>,");
+        println!("{{");
+        println!(
+            "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<U> {{"
+        );
+        println!("        let value = T::arbitrary_as(u)?;");
+        println!();
+        println!("        // Values not matching the pattern are rejected rather than fixed up");
+        println!("        let wrapped = serde_with::ser::SerializeAsWrap::<U, T>::new(&value);");
+        println!("        match serde_json::to_value(wrapped) {{");
+        println!("            Ok(serde_json::Value::String(serialized)) if P::regex().is_match(&serialized) => {{}}");
+        println!("            _ => return Err(arbitrary::Error::IncorrectFormat),");
+        println!("        }}");
+        println!();
+        println!("        Ok(value)");
+        println!("    }}");
+        println!("}}");
+        println!();
+        println!("#[cfg(all(feature = \"arbitrary\", not(feature = \"validate_patterns\")))]");
+        println!("impl<'a, P, T, U> ArbitraryAs<'a, U> for Validated<P, T>");
+        println!("where");
+        println!("    T: ArbitraryAs<'a, U>,");
+        println!("{{");
+        println!(
+            "    fn arbitrary_as(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<U> {{"
+        );
+        println!("        T::arbitrary_as(u)");
+        println!("    }}");
+        println!("}}");
+    }
+}
+
+/// Renders the type holding entries of maps (de)serialized with `MerkleNodeMap`, whose schema
+/// describes them.
+fn render_merkle_node_map_entry_stdout() {
+    println!("#[cfg(feature = \"schemars\")]");
+    println!("#[derive(schemars::JsonSchema)]");
+    println!("#[allow(dead_code)]");
+    println!("struct MerkleNodeMapEntry<T> {{");
+    println!("    #[schemars(with = \"String\")]");
+    println!("    node_hash: Felt,");
+    println!("    node: T,");
+    println!("}}");
+}
+
+/// Renders `spec_schemas`, which maps specification schemas to the schemas of their generated
+/// types for the `verify` command.
+fn render_spec_schemas_stdout(specs: &Specification, types: &[&RustType], aliases: &[RustAlias]) {
+    let type_names = types
+        .iter()
+        .map(|rust_type| rust_type.name.as_str())
+        .collect::<HashSet<_>>();
+
+    print_doc(
+        "Schemas of the generated types keyed by the specification schemas they implement, along \
+        with the definitions they reference.",
+        0,
+    );
+    println!("#[cfg(feature = \"schemars\")]");
+    println!("pub fn spec_schemas() -> serde_json::Value {{");
+    println!("    let mut generator = schemars::SchemaGenerator::default();");
+    println!("    let mut schemas = serde_json::Map::new();");
+    println!();
+
+    for name in specs.components.schemas.keys() {
+        let rust_name = to_starknet_rs_name(name);
+        // Aliases of handwritten types are left out like the types themselves
+        let is_generated = type_names.contains(rust_name.as_str())
+            || aliases.iter().any(|alias| {
+                alias.name == rust_name && type_names.contains(alias.content.src_name.as_str())
+            });
+        if !is_generated {
+            continue;
+        }
+
+        let schema = format!("generator.subschema_for::<{rust_name}>().to_value()");
+        let args = format!("\"{name}\".into(), {schema}");
+        if args.len() <= FN_CALL_WIDTH {
+            println!("    schemas.insert({args});");
+        } else {
+            println!("    schemas.insert(");
+            println!("        \"{name}\".into(),");
+            if schema.len() <= CHAIN_WIDTH {
+                println!("        {schema},");
+            } else {
+                println!("        generator");
+                println!("            .subschema_for::<{rust_name}>()");
+                println!("            .to_value(),");
+            }
+            println!("    );");
+        }
+    }
+
+    println!();
+    println!("    serde_json::json!({{");
+    println!("        \"schemas\": schemas,");
+    println!("        \"$defs\": generator.definitions(),");
+    println!("    }})");
+    println!("}}");
+}
+
+/// Renders a test module checking that synthesized samples of generated types survive a serde
+/// round trip, and that fixed field values and positional param counts are enforced.
+fn render_serde_tests_stdout(types: &[&RustType], aliases: &[RustAlias], patterns: &[PatternRule]) {
+    let mut synthesizer = SampleSynthesizer::new(types, aliases, patterns);
+
+    let mut samples = vec![];
+    let mut unsampled_types = vec![];
+    for rust_type in types.iter() {
+        match synthesizer.for_type(&rust_type.name) {
+            Some(sample) => samples.push((*rust_type, sample)),
+            None => unsampled_types.push(rust_type.name.as_str()),
+        }
+    }
+
+    let uses_version_offsets = samples
+        .iter()
+        .any(|(rust_type, _)| match &rust_type.content {
+            RustTypeKind::Struct(content) => content.fields.iter().any(|field| {
+                field
+                    .fixed
+                    .as_ref()
+                    .is_some_and(|fixed| !fixed.version_offsets.is_empty())
+            }),
+            _ => false,
+        });
+
+    println!("#[cfg(test)]");
+    println!("mod tests {{");
+
+    if !unsampled_types.is_empty() {
+        println!("    // No sample could be synthesized for these types:");
+        for type_name in unsampled_types.iter() {
+            println!("    // - `{type_name}`");
+        }
+        println!();
+    }
+
+    println!("    use super::*;");
+    if uses_version_offsets {
+        println!("    use serde_with::ser::SerializeAsWrap;");
+    }
+    println!();
+    println!("    /// Checks that `input` deserializes into a value serializing into `expected`.");
+    println!("    fn assert_decodes_to<T>(input: serde_json::Value, expected: &serde_json::Value)");
+    println!("    where");
+    println!("        T: Serialize + serde::de::DeserializeOwned,");
+    println!("    {{");
+    println!("        let value = serde_json::from_value::<T>(input).unwrap();");
+    println!("        assert_eq!(&serde_json::to_value(&value).unwrap(), expected);");
+    println!("    }}");
+    println!();
+    println!(
+        "    /// Checks that serializing the value deserialized from `sample` gives `sample` back."
+    );
+    println!("    fn assert_round_trip<T>(sample: serde_json::Value)");
+    println!("    where");
+    println!("        T: Serialize + serde::de::DeserializeOwned,");
+    println!("    {{");
+    println!("        assert_decodes_to::<T>(sample.clone(), &sample);");
+    println!("    }}");
+    println!();
+    println!("    /// Checks that `input` fails to deserialize.");
+    println!("    fn assert_rejected<T>(input: serde_json::Value)");
+    println!("    where");
+    println!("        T: serde::de::DeserializeOwned + core::fmt::Debug,");
+    println!("    {{");
+    println!("        let result = serde_json::from_value::<T>(input);");
+    println!("        assert!(result.is_err(), \"unexpectedly deserialized: {{result:?}}\");");
+    println!("    }}");
+
+    for (rust_type, sample) in samples.iter() {
+        let name = &rust_type.name;
+        let test_name = pascal_to_snake_case(name);
+
+        println!();
+        print_json_sample_fn(&format!("sample_{test_name}"), sample);
+        println!();
+        println!("    #[test]");
+        println!("    fn {test_name}_round_trip() {{");
+        print_call_stdout(
+            8,
+            &format!("assert_round_trip::<{name}>"),
+            &[format!("sample_{test_name}()")],
+        );
+        println!("    }}");
+
+        match &rust_type.content {
+            RustTypeKind::Struct(content) => {
+                render_fixed_field_tests_stdout(name, content, sample, &synthesizer);
+
+                if let Some(
+                    param_structure @ (ParamStructure::ByPosition | ParamStructure::Either),
+                ) = content.param_structure
+                {
+                    let positional_sample_fn = if param_structure == ParamStructure::Either {
+                        let positional_sample = synthesizer
+                            .for_positional_params(content)
+                            .expect("positional sample to exist along with the named one");
+
+                        println!();
+                        print_json_sample_fn(
+                            &format!("positional_sample_{test_name}"),
+                            &positional_sample,
+                        );
+                        println!();
+                        println!("    #[test]");
+                        println!("    fn {test_name}_positional_params() {{");
+                        print_call_stdout(
+                            8,
+                            &format!("assert_decodes_to::<{name}>"),
+                            &[
+                                format!("positional_sample_{test_name}()"),
+                                format!("&sample_{test_name}()"),
+                            ],
+                        );
+                        println!("    }}");
+
+                        format!("positional_sample_{test_name}")
+                    } else {
+                        format!("sample_{test_name}")
+                    };
 
-                            fields.push(RustField {
-                                description: reference.description.to_owned(),
-                                name: field_name,
-                                optional: false,
-                                fixed: None,
-                                arc_wrap: false,
-                                type_name: to_starknet_rs_name(reference.name()),
-                                serde_rename: None,
-                                serde_flatten: true,
-                                serializer: None,
-                            });
-                        }
-                    }
-                    _ => {
-                        // We don't have a choice but to flatten it
-                        get_schema_fields(item, specs, fields, flatten_option)?;
+                    println!();
+                    println!("    #[test]");
+                    println!("    fn {test_name}_rejects_extra_positional_param() {{");
+                    println!("        let mut params = {positional_sample_fn}();");
+                    println!(
+                        "        params.as_array_mut().unwrap().push(serde_json::Value::Null);"
+                    );
+                    println!("        assert_rejected::<{name}>(params);");
+                    println!("    }}");
+
+                    // Params after the last required one can be left out
+                    if let Some(required_count) =
+                        content.fields.iter().rposition(|field| !field.optional)
+                    {
+                        println!();
+                        println!("    #[test]");
+                        println!("    fn {test_name}_rejects_missing_positional_param() {{");
+                        println!("        let mut params = {positional_sample_fn}();");
+                        println!(
+                            "        params.as_array_mut().unwrap().truncate({required_count});"
+                        );
+                        println!("        assert_rejected::<{name}>(params);");
+                        println!("    }}");
                     }
                 }
             }
+            RustTypeKind::Unit(RustUnit {
+                param_structure: Some(ParamStructure::ByPosition | ParamStructure::Either),
+            }) => {
+                println!();
+                println!("    #[test]");
+                println!("    fn {test_name}_rejects_extra_positional_param() {{");
+                println!("        assert_rejected::<{name}>(serde_json::json!([null]));");
+                println!("    }}");
+            }
+            _ => {}
         }
-        Schema::Primitive(Primitive::Object(value)) => {
-            for (name, prop_value) in value.properties.iter() {
-                // For fields we keep things simple and only use one line
-                let doc_string = match prop_value.description() {
-                    Some(text) => Some(text),
-                    None => match prop_value.title() {
-                        Some(text) => Some(text),
-                        None => prop_value.summary(),
-                    },
-                };
+    }
 
-                let field_type = get_rust_type_for_field(prop_value)?;
+    println!("}}");
+}
 
-                let field_name = to_rust_field_name(name);
-                let rename = if name == &field_name {
-                    None
-                } else {
-                    Some(name.to_owned())
-                };
+/// Renders tests replacing the value of each fixed field of a struct, expecting wrong values to be
+/// rejected and values with version offsets applied to be accepted only when exact.
+fn render_fixed_field_tests_stdout(
+    name: &str,
+    content: &RustStruct,
+    sample: &serde_json::Value,
+    synthesizer: &SampleSynthesizer,
+) {
+    let test_name = pascal_to_snake_case(name);
 
-                // Optional field transformation
-                let field_optional = !value.required.contains(name);
-                let serializer = if field_optional {
-                    field_type.serializer.map(|value| value.to_optional())
-                } else {
-                    field_type.serializer
-                };
+    for field in content.fields.iter() {
+        let Some(fixed) = &field.fixed else {
+            continue;
+        };
+        let key = field.serde_rename.as_ref().unwrap_or(&field.name);
+        let Some(fixed_value) = sample.get(key) else {
+            continue;
+        };
+        let field_name = field.name.to_ascii_lowercase();
 
-                fields.push(RustField {
-                    description: doc_string.map(|value| to_starknet_rs_doc(value, false)),
-                    name: field_name,
-                    optional: field_optional,
-                    fixed: None,
-                    arc_wrap: false,
-                    type_name: field_type.type_name,
-                    serde_rename: rename,
-                    serde_flatten: false,
-                    serializer,
-                });
+        println!();
+        println!("    #[test]");
+        println!("    fn {test_name}_rejects_wrong_{field_name}() {{");
+        println!("        let mut sample = sample_{test_name}();");
+        println!(
+            "        sample[\"{}\"] = serde_json::json!({});",
+            key,
+            synthesizer.wrong_fixed_value(field, fixed_value)
+        );
+        println!("        assert_rejected::<{name}>(sample);");
+        println!("    }}");
+
+        // Fields with version offsets are always (de)serialized as `Felt` through `UfeHex`
+        let value = fixed.value.trim_start_matches('&');
+        let to_value = "serde_json::to_value(SerializeAsWrap::<_, UfeHex>::new(&value)).unwrap()";
+
+        for offset in content.offsets_of(fixed) {
+            let offset_name = offset.name.trim_end_matches("_OFFSET").to_ascii_lowercase();
+
+            for (accepted, offset_expr) in [
+                (true, offset.name.clone()),
+                (false, format!("{} + Felt::ONE", offset.name)),
+            ] {
+                println!();
+                println!("    #[test]");
+                if accepted {
+                    println!("    fn {test_name}_accepts_{offset_name}() {{");
+                } else {
+                    println!("    fn {test_name}_rejects_invalid_{offset_name}() {{");
+                }
+                println!("        let value = {value} + {offset_expr};");
+                println!("        let mut sample = sample_{test_name}();");
+                let line = format!("        sample[\"{key}\"] = {to_value};");
+                if line.len() <= MAX_LINE_LENGTH {
+                    println!("{line}");
+                } else {
+                    println!("        sample[\"{key}\"] =");
+                    println!("            {to_value};");
+                }
+                if accepted {
+                    println!("        assert_round_trip::<{name}>(sample);");
+                } else {
+                    println!("        assert_rejected::<{name}>(sample);");
+                }
+                println!("    }}");
             }
         }
-        _ => {
-            dbg!(schema);
-            anyhow::bail!("Unexpected schema type when getting object fields");
-        }
     }
+}
 
-    Ok(())
+/// Prints a statement calling `callee`, moving the arguments to their own lines when too long.
+fn print_call_stdout(indent_spaces: usize, callee: &str, args: &[String]) {
+    let indent = " ".repeat(indent_spaces);
+    let line = format!("{indent}{callee}({});", args.join(", "));
+    // rustfmt lets a lone argument calling a function without arguments overflow by the semicolon
+    let limit = match args {
+        [arg] if arg.find('(') == Some(arg.len() - 2) => MAX_LINE_LENGTH + 1,
+        _ => MAX_LINE_LENGTH,
+    };
+    if line.len() <= limit {
+        println!("{line}");
+    } else {
+        println!("{indent}{callee}(");
+        for arg in args.iter() {
+            println!("{indent}    {arg},");
+        }
+        println!("{indent});");
+    }
 }
 
-fn get_rust_type_for_field(schema: &Schema) -> Result<RustFieldType> {
-    match schema {
-        Schema::Ref(value) => {
-            let ref_type_name = value.name();
+/// Prints a test helper function returning a sample as a `serde_json::json!` literal.
+fn print_json_sample_fn(fn_name: &str, sample: &serde_json::Value) {
+    println!("    fn {fn_name}() -> serde_json::Value {{");
 
-            if let Some(type_override) = get_field_type_override(ref_type_name) {
-                // Hard-coded special rules
-                Ok(type_override)
-            } else {
-                // TODO: take non-alias refs into account
-                Ok(RustFieldType {
-                    type_name: to_starknet_rs_name(ref_type_name),
-                    serializer: None,
-                })
-            }
-        }
-        Schema::OneOf(_) => {
-            anyhow::bail!("Anonymous oneOf types should not be used for properties");
-        }
-        Schema::AllOf(_) => {
-            anyhow::bail!("Anonymous allOf types should not be used for properties");
+    // Samples without objects are valid Rust expressions, which rustfmt puts on one line when
+    // they fit
+    if let Some(json) = compact_json(sample) {
+        let line = format!("        serde_json::json!({json})");
+        if line.len() <= MAX_LINE_LENGTH && json.len() <= ARRAY_WIDTH {
+            println!("{line}");
+            println!("    }}");
+            return;
         }
-        Schema::Primitive(value) => match value {
-            Primitive::Array(value) => {
-                let item_type = get_rust_type_for_field(&value.items)?;
-                let serializer = match item_type.serializer {
-                    Some(SerializerOverride::Serde(_)) => {
-                        todo!("Array wrapper for #[serde(with)] not implemented")
-                    }
-                    Some(SerializerOverride::SerdeAs(serializer)) => {
-                        Some(SerializerOverride::SerdeAs(format!("Vec<{serializer}>")))
-                    }
-                    None => None,
-                };
-                Ok(RustFieldType {
-                    type_name: format!("Vec<{}>", item_type.type_name),
-                    serializer,
-                })
-            }
-            Primitive::Boolean(_) => Ok(RustFieldType {
-                type_name: String::from("bool"),
-                serializer: None,
-            }),
-            Primitive::Integer(_) => Ok(RustFieldType {
-                type_name: String::from("u64"),
-                serializer: None,
-            }),
-            Primitive::Object(_) => {
-                anyhow::bail!("Anonymous object types should not be used for properties");
-            }
-            Primitive::String(value) => {
-                // Hacky solution but it's the best we can do given the specs
-                if let Some(desc) = &value.description {
-                    if desc.contains("base64") {
-                        return Ok(RustFieldType {
-                            type_name: String::from("Vec<u8>"),
-                            serializer: Some(SerializerOverride::Serde(String::from("base64"))),
-                        });
-                    }
-                }
+    }
 
-                Ok(RustFieldType {
-                    type_name: String::from("String"),
-                    serializer: None,
-                })
-            }
-        },
+    let mut json = vec![];
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+    serde::Serialize::serialize(sample, &mut serializer).expect("sample to serialize");
+    let json = String::from_utf8(json).expect("JSON to be UTF-8");
+
+    let lines = json.lines().collect::<Vec<_>>();
+
+    for (ind, line) in lines.iter().enumerate() {
+        let prefix = if ind == 0 { "serde_json::json!(" } else { "" };
+        let suffix = if ind == lines.len() - 1 { ")" } else { "" };
+        println!("        {prefix}{line}{suffix}");
     }
+    println!("    }}");
 }
 
-fn get_field_type_override(type_name: &str) -> Option<RustFieldType> {
-    Some(match type_name {
-        "ADDRESS" | "STORAGE_KEY" | "TXN_HASH" | "FELT" | "BLOCK_HASH" | "CHAIN_ID"
-        | "PROTOCOL_VERSION" => RustFieldType {
-            type_name: String::from("Felt"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("UfeHex"))),
-        },
-        "ETH_ADDRESS" => RustFieldType {
-            type_name: String::from("EthAddress"),
-            serializer: None,
-        },
-        "EXECUTION_RESULT" => RustFieldType {
-            type_name: String::from("ExecutionResult"),
-            serializer: None,
-        },
-        "BLOCK_NUMBER" => RustFieldType {
-            type_name: String::from("u64"),
-            serializer: None,
-        },
-        "NUM_AS_HEX" => RustFieldType {
-            type_name: String::from("u64"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
-        },
-        "SIGNATURE" => RustFieldType {
-            type_name: String::from("Vec<Felt>"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("Vec<UfeHex>"))),
-        },
-        "EVENT_KEYS" => RustFieldType {
-            type_name: String::from("Vec<Vec<Felt>>"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from(
-                "Vec<Vec<UfeHex>>",
-            ))),
-        },
-        "NODE_HASH_TO_NODE_MAPPING" => RustFieldType {
-            type_name: String::from("IndexMap<Felt, MerkleNode, RandomState>"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("MerkleNodeMap"))),
-        },
-        "CONTRACT_ABI" => RustFieldType {
-            type_name: String::from("Vec<LegacyContractAbiEntry>"),
-            serializer: None,
-        },
-        "CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
-            type_name: String::from("Vec<ContractEntryPoint>"),
-            serializer: None,
-        },
-        "LEGACY_CONTRACT_ENTRY_POINT_LIST" => RustFieldType {
-            type_name: String::from("Vec<LegacyContractEntryPoint>"),
-            serializer: None,
-        },
-        "TXN_TYPE" => RustFieldType {
-            type_name: String::from("String"),
-            serializer: None,
-        },
-        "NESTED_CALL" => RustFieldType {
-            type_name: String::from("FunctionInvocation"),
-            serializer: None,
-        },
-        "HASH_256" | "L1_TXN_HASH" => RustFieldType {
-            type_name: String::from("Hash256"),
-            serializer: None,
-        },
-        "u64" => RustFieldType {
-            type_name: String::from("u64"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
-        },
-        "u128" => RustFieldType {
-            type_name: String::from("u128"),
-            serializer: Some(SerializerOverride::SerdeAs(String::from("NumAsHex"))),
-        },
-        "SUBSCRIPTION_BLOCK_ID" => RustFieldType {
-            type_name: String::from("ConfirmedBlockId"),
-            serializer: None,
-        },
-        "TXN_STATUS_RESULT" => RustFieldType {
-            type_name: String::from("TransactionStatus"),
-            serializer: None,
-        },
-        _ => return None,
-    })
+/// Formats a JSON value on one line the way rustfmt formats Rust expressions, unless it contains
+/// objects, which aren't Rust expressions.
+fn compact_json(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Object(_) => None,
+        serde_json::Value::Array(items) => Some(format!(
+            "[{}]",
+            items
+                .iter()
+                .map(compact_json)
+                .collect::<Option<Vec<_>>>()?
+                .join(", ")
+        )),
+        _ => Some(value.to_string()),
+    }
 }
 
-fn get_all_of_ref_name_override(type_name: &str) -> Option<String> {
-    match type_name {
-        "TXN_RECEIPT" => Some("receipt".into()),
-        "RECEIPT_BLOCK" => Some("block".into()),
-        _ => None,
-    }
+fn render_parse_enum_error_stdout() {
+    println!("/// Error parsing a string enum from a value not defined in the specification.");
+    println!("#[derive(Debug, Clone, PartialEq, Eq)]");
+    println!("pub struct ParseEnumError {{");
+    println!("    /// The value that failed to parse");
+    println!("    pub value: String,");
+    println!("    /// Values accepted by the enum");
+    println!("    pub accepted: &'static [&'static str],");
+    println!("}}");
+    println!();
+    println!("#[cfg(feature = \"std\")]");
+    println!("impl std::error::Error for ParseEnumError {{}}");
+    println!();
+    println!("impl core::fmt::Display for ParseEnumError {{");
+    println!("    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{");
+    println!("        write!(f, \"invalid value `{{}}`, expected one of:\", self.value)?;");
+    println!("        for (ind, value) in self.accepted.iter().enumerate() {{");
+    println!(
+        "            write!(f, \"{{}} `{{}}`\", if ind == 0 {{ \"\" }} else {{ \",\" }}, value)?;"
+    );
+    println!("        }}");
+    println!("        Ok(())");
+    println!("    }}");
+    println!("}}");
 }
 
 fn print_doc(doc: &str, indent_spaces: usize) {
@@ -1992,6 +6687,13 @@ fn to_pascal_case(name: &str) -> String {
     result
 }
 
+/// Name of the `Method` variant of a method, which prefixes its request type name.
+fn to_method_variant_name(method_name: &str) -> String {
+    to_starknet_rs_name(&camel_to_snake_case(
+        method_name.trim_start_matches("starknet_"),
+    ))
+}
+
 fn camel_to_snake_case(name: &str) -> String {
     let mut result = String::new();
 
@@ -2008,6 +6710,32 @@ fn camel_to_snake_case(name: &str) -> String {
     result
 }
 
+/// Converts type names like `InvokeTransactionV3` to `invoke_transaction_v3`.
+fn pascal_to_snake_case(name: &str) -> String {
+    let mut result = String::new();
+
+    for (ind, character) in name.chars().enumerate() {
+        if character.is_ascii_uppercase() {
+            if ind != 0 {
+                result.push('_');
+            }
+            result.push(character.to_ascii_lowercase());
+        } else {
+            result.push(character);
+        }
+    }
+
+    result
+}
+
+/// Gets the type argument of a generic type like `Vec<T>` named `name`.
+fn strip_generic<'a>(type_name: &'a str, name: &str) -> Option<&'a str> {
+    type_name
+        .strip_prefix(name)?
+        .strip_prefix('<')?
+        .strip_suffix('>')
+}
+
 fn to_sentence_case(name: &str) -> String {
     let mut result = String::new();
 
@@ -2044,8 +6772,85 @@ fn escape_name(name: &str) -> &str {
     }
 }
 
+fn print_json_schema_derive() {
+    println!("#[cfg_attr(feature = \"schemars\", derive(schemars::JsonSchema))]");
+}
+
 fn print_rust_derives(derives: &IndexSet<String>) {
     if !derives.is_empty() {
         println!("#[derive({})]", itertools::join(derives, ", "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::RawSpecs;
+
+    use super::*;
+
+    /// Gets the fields and enum variants boxed automatically in the types of the latest profile.
+    fn auto_boxed_of_latest_profile() -> Vec<String> {
+        let raw_specs = RawSpecs {
+            main: include_str!("../specs/0.9.0/starknet_api_openrpc.json"),
+            write: include_str!("../specs/0.9.0/starknet_write_api.json"),
+            trace: include_str!("../specs/0.9.0/starknet_trace_api_openrpc.json"),
+            ws: Some(include_str!("../specs/0.9.0/starknet_ws_api.json")),
+        };
+        let options: ProfileOptions =
+            serde_json::from_str(include_str!("../profiles/0.9.0.json")).unwrap();
+        let result = resolve_types(&raw_specs.parse_full().unwrap(), &options, false).unwrap();
+
+        let mut boxed = vec![];
+        for item in result
+            .model_types
+            .iter()
+            .chain(result.request_response_types.iter())
+        {
+            match &item.content {
+                RustTypeKind::Struct(inner) => boxed.extend(
+                    inner
+                        .fields
+                        .iter()
+                        .filter(|field| field.auto_boxed)
+                        .map(|field| format!("{}.{}", item.name, field.name)),
+                ),
+                RustTypeKind::Enum(inner) => boxed.extend(
+                    inner
+                        .variants
+                        .iter()
+                        .filter(|variant| {
+                            variant.boxed
+                                && !options
+                                    .arc_wrapped_types
+                                    .in_field_wrapped(&item.name, &variant.name)
+                        })
+                        .map(|variant| format!("{}::{}", item.name, variant.name)),
+                ),
+                _ => {}
+            }
+        }
+
+        boxed
+    }
+
+    #[test]
+    fn latest_profile_boxes_nothing_automatically() {
+        assert_eq!(auto_boxed_of_latest_profile(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn options_of_niche_types() {
+        let types = HashMap::new();
+        let mut sizes = HashMap::new();
+
+        assert_eq!(estimate_type_size("Option<u64>", &types, &mut sizes), 16);
+        assert_eq!(
+            estimate_type_size("Option<core::num::NonZeroU64>", &types, &mut sizes),
+            8
+        );
+        assert_eq!(
+            estimate_type_size("Option<OwnedPtr<Felt>>", &types, &mut sizes),
+            8
+        );
+    }
+}
@@ -3,3 +3,6 @@ pub use generate::Generate;
 
 mod print;
 pub use print::Print;
+
+mod verify;
+pub use verify::Verify;
@@ -0,0 +1,765 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::Parser;
+use serde_json::{Map, Value};
+
+use crate::{GenerationProfile, SpecVersion};
+
+#[derive(Debug, Parser)]
+pub struct Verify {
+    #[clap(long, env, help = "Version of the specification")]
+    spec: SpecVersion,
+    #[clap(
+        long,
+        help = "Path to the JSON file holding the output of `spec_schemas()` from code generated \
+                with `--json-schema`"
+    )]
+    schemas: PathBuf,
+}
+
+/// Keywords not affecting which JSON values are (de)serialized. String patterns and integer
+/// bounds are checked on deserialization by `--validate-patterns` instead.
+const IGNORED_KEYWORDS: [&str; 18] = [
+    "$comment",
+    "$schema",
+    "default",
+    "deprecated",
+    "description",
+    "examples",
+    "exclusiveMaximum",
+    "exclusiveMinimum",
+    "format",
+    "maxItems",
+    "maximum",
+    "minItems",
+    "minimum",
+    "not",
+    "pattern",
+    "summary",
+    "title",
+    "writeOnly",
+];
+
+/// Schemas along with the definitions their references point to.
+struct SchemaDocument {
+    definitions: Map<String, Value>,
+}
+
+/// Comparison of specification schemas with the schemas derived from generated types.
+struct SchemaComparison<'a> {
+    spec: &'a SchemaDocument,
+    derived: &'a SchemaDocument,
+    /// Pairs of referenced definitions already being compared, which breaks recursion.
+    visited: HashSet<(String, String)>,
+    mismatches: Vec<String>,
+}
+
+/// The part of a schema describing accepted JSON values.
+#[derive(Debug, Clone)]
+enum Shape<'a> {
+    Any,
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    /// Only the listed values, serialized, from `enum` or `const`.
+    Values(BTreeSet<String>),
+    Array(&'a Value),
+    Object {
+        properties: BTreeMap<&'a str, &'a Value>,
+        required: BTreeSet<&'a str>,
+        additional_properties: Option<&'a Value>,
+    },
+    Union(Vec<Shape<'a>>),
+}
+
+impl Verify {
+    pub(crate) fn run(self, profiles: &[GenerationProfile]) -> Result<()> {
+        let profile = profiles
+            .iter()
+            .find(|profile| profile.version == self.spec)
+            .expect("Unable to find profile");
+
+        let specs = profile
+            .raw_specs
+            .parse_full()
+            .expect("Failed to parse specification");
+
+        let derived: Value = serde_json::from_str(&std::fs::read_to_string(&self.schemas)?)?;
+        let (Some(Value::Object(derived_schemas)), Some(Value::Object(derived_definitions))) =
+            (derived.get("schemas"), derived.get("$defs"))
+        else {
+            anyhow::bail!("Expected an object with `schemas` and `$defs`");
+        };
+
+        let spec = SchemaDocument::new(
+            specs
+                .components
+                .schemas
+                .iter()
+                .map(|(name, schema)| Ok((name.to_owned(), serde_json::to_value(schema)?)))
+                .collect::<Result<_>>()?,
+        );
+        let derived = SchemaDocument::new(derived_definitions.clone());
+
+        let mut mismatch_count = 0;
+        for (name, derived_schema) in derived_schemas.iter() {
+            let Some(spec_schema) = spec.definitions.get(name) else {
+                anyhow::bail!("Schema {name} not found in the specification");
+            };
+
+            let mut comparison = SchemaComparison {
+                spec: &spec,
+                derived: &derived,
+                visited: HashSet::new(),
+                mismatches: vec![],
+            };
+            comparison.compare(
+                spec_schema,
+                &SchemaDocument::normalize(derived_schema.clone()),
+                name,
+                false,
+            );
+
+            for mismatch in comparison.mismatches.iter() {
+                println!("{mismatch}");
+            }
+            mismatch_count += comparison.mismatches.len();
+        }
+
+        if mismatch_count != 0 {
+            anyhow::bail!("{mismatch_count} mismatches found");
+        }
+
+        println!("{} schemas match the specification", derived_schemas.len());
+
+        Ok(())
+    }
+}
+
+impl SchemaDocument {
+    pub fn new(definitions: Map<String, Value>) -> Self {
+        Self {
+            definitions: definitions
+                .into_iter()
+                .map(|(name, schema)| (name, Self::normalize(schema)))
+                .collect(),
+        }
+    }
+
+    /// Removes ignored keywords and turns multiple types into `anyOf`, so that both sides express
+    /// nullable values the same way.
+    fn normalize(schema: Value) -> Value {
+        match schema {
+            Value::Object(object) => {
+                let mut object = object
+                    .into_iter()
+                    .filter(|(key, _)| !IGNORED_KEYWORDS.contains(&key.as_str()))
+                    .map(|(key, value)| match key.as_str() {
+                        // Property names are not schemas
+                        "properties" | "$defs" => (
+                            key,
+                            match value {
+                                Value::Object(properties) => Value::Object(
+                                    properties
+                                        .into_iter()
+                                        .map(|(name, value)| (name, Self::normalize(value)))
+                                        .collect(),
+                                ),
+                                value => value,
+                            },
+                        ),
+                        "enum" | "const" | "required" => (key, value),
+                        _ => (key, Self::normalize(value)),
+                    })
+                    .collect::<Map<_, _>>();
+
+                if let Some(Value::Array(types)) = object.get("type").cloned() {
+                    object.remove("type");
+                    object = Map::from_iter([(
+                        String::from("anyOf"),
+                        Value::Array(
+                            types
+                                .into_iter()
+                                .map(|item| {
+                                    let mut variant = object.clone();
+                                    variant.insert(String::from("type"), item);
+                                    Value::Object(variant)
+                                })
+                                .collect(),
+                        ),
+                    )]);
+                }
+
+                Value::Object(object)
+            }
+            Value::Array(items) => Value::Array(items.into_iter().map(Self::normalize).collect()),
+            value => value,
+        }
+    }
+
+    /// Follows references, returning the referenced definition name if any.
+    fn resolve<'a>(
+        &'a self,
+        mut schema: &'a Value,
+    ) -> Result<(&'a Value, Option<&'a str>), String> {
+        let mut name = None;
+        while let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = match reference.rfind('/') {
+                Some(ind_slash) => &reference[ind_slash + 1..],
+                None => reference,
+            };
+            if name == Some(target) {
+                return Err(format!("circular reference {reference}"));
+            }
+            schema = self
+                .definitions
+                .get(target)
+                .ok_or_else(|| format!("unresolved reference {reference}"))?;
+            name = Some(target);
+        }
+        Ok((schema, name))
+    }
+
+    fn shape_of<'a>(&'a self, schema: &'a Value) -> Result<Shape<'a>, String> {
+        let (schema, _) = self.resolve(schema)?;
+        let object = match schema {
+            Value::Bool(true) => return Ok(Shape::Any),
+            Value::Object(object) => object,
+            _ => return Err(format!("unsupported schema {schema}")),
+        };
+
+        if let Some(values) = object.get("enum").and_then(Value::as_array) {
+            return Ok(Shape::Values(values.iter().map(Value::to_string).collect()));
+        }
+        if let Some(value) = object.get("const") {
+            return Ok(Shape::Values(BTreeSet::from([value.to_string()])));
+        }
+
+        let mut properties = BTreeMap::new();
+        let mut required = BTreeSet::new();
+        let mut additional_properties = None;
+        let mut is_object = false;
+
+        if let Some(Value::Object(object_properties)) = object.get("properties") {
+            properties.extend(
+                object_properties
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value)),
+            );
+            is_object = true;
+        }
+        if let Some(Value::Array(object_required)) = object.get("required") {
+            required.extend(object_required.iter().filter_map(Value::as_str));
+            is_object = true;
+        }
+        if let Some(value) = object.get("additionalProperties") {
+            if value != &Value::Bool(false) {
+                additional_properties = Some(value);
+            }
+            is_object = true;
+        }
+
+        let mut shape = match object.get("type").and_then(Value::as_str) {
+            Some("null") => Shape::Null,
+            Some("boolean") => Shape::Boolean,
+            Some("integer") => Shape::Integer,
+            Some("number") => Shape::Number,
+            Some("string") => Shape::String,
+            Some("array") => Shape::Array(object.get("items").unwrap_or(&Value::Bool(true))),
+            Some("object") => Shape::Object {
+                properties,
+                required,
+                additional_properties,
+            },
+            Some(value) => return Err(format!("unsupported type {value}")),
+            None if is_object => Shape::Object {
+                properties,
+                required,
+                additional_properties,
+            },
+            None => Shape::Any,
+        };
+
+        if let Some(parts) = object.get("allOf") {
+            let parts = parts
+                .as_array()
+                .ok_or_else(|| String::from("parts not in an array"))?;
+            for part in parts.iter() {
+                shape = shape.merge(self.shape_of(part)?)?;
+            }
+        }
+
+        if let Some(variants) = object.get("oneOf").or_else(|| object.get("anyOf")) {
+            let variants = variants
+                .as_array()
+                .ok_or_else(|| String::from("variants not in an array"))?
+                .iter()
+                .map(|variant| self.shape_of(variant))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // Unions of values, like string enums with documented variants, are sets of values
+            let union = if variants
+                .iter()
+                .all(|variant| matches!(variant, Shape::Values(_)))
+            {
+                Shape::Values(
+                    variants
+                        .into_iter()
+                        .flat_map(|variant| match variant {
+                            Shape::Values(values) => values,
+                            _ => BTreeSet::new(),
+                        })
+                        .collect(),
+                )
+            } else {
+                Shape::union(variants)
+            };
+            shape = shape.merge(union)?;
+        }
+
+        Ok(shape)
+    }
+}
+
+impl SchemaComparison<'_> {
+    /// Compares the schemas of the values found at `path`. Values that are `optional` may be left
+    /// out, which makes `null` alternatives of derived schemas for `Option` fields irrelevant.
+    fn compare(&mut self, spec_schema: &Value, derived_schema: &Value, path: &str, optional: bool) {
+        let (spec, derived) = (self.spec, self.derived);
+
+        let ((spec_schema, spec_name), (derived_schema, derived_name)) =
+            match (spec.resolve(spec_schema), derived.resolve(derived_schema)) {
+                (Ok(spec_resolved), Ok(derived_resolved)) => (spec_resolved, derived_resolved),
+                (Err(err), _) | (_, Err(err)) => return self.mismatch(path, err),
+            };
+        if let (Some(spec_name), Some(derived_name)) = (spec_name, derived_name) {
+            if !self
+                .visited
+                .insert((spec_name.to_owned(), derived_name.to_owned()))
+            {
+                return;
+            }
+        }
+
+        match (spec.shape_of(spec_schema), derived.shape_of(derived_schema)) {
+            (Ok(spec_shape), Ok(derived_shape)) => {
+                self.compare_shapes(spec_shape, derived_shape, path, optional)
+            }
+            (Err(err), _) | (_, Err(err)) => self.mismatch(path, err),
+        }
+    }
+
+    fn compare_shapes(
+        &mut self,
+        spec_shape: Shape,
+        derived_shape: Shape,
+        path: &str,
+        optional: bool,
+    ) {
+        let derived_shape = match derived_shape {
+            Shape::Union(variants) if optional => Shape::union(
+                variants
+                    .into_iter()
+                    .filter(|variant| !matches!(variant, Shape::Null))
+                    .collect(),
+            ),
+            shape => shape,
+        };
+
+        match (spec_shape, derived_shape) {
+            (Shape::Union(spec_variants), Shape::Union(derived_variants)) => {
+                self.compare_variants(spec_variants, derived_variants, path)
+            }
+            (Shape::Values(spec_values), Shape::Values(derived_values)) => {
+                // A single value is what fixed fields pin an enum down to
+                let is_fixed = derived_values.len() == 1 && derived_values.is_subset(&spec_values);
+                if spec_values != derived_values && !is_fixed {
+                    self.mismatch(
+                        path,
+                        format!(
+                            "expected values {}, found {}",
+                            itertools::join(spec_values, ", "),
+                            itertools::join(derived_values, ", ")
+                        ),
+                    );
+                }
+            }
+            // Fixed fields narrow values down without changing their type
+            (spec_shape, Shape::Values(derived_values)) if spec_shape.accepts(&derived_values) => {}
+            (Shape::Array(spec_items), Shape::Array(derived_items)) => {
+                self.compare(spec_items, derived_items, &format!("{path}[]"), false)
+            }
+            (
+                Shape::Object {
+                    properties: spec_properties,
+                    required: spec_required,
+                    additional_properties: spec_additional_properties,
+                },
+                Shape::Object {
+                    properties: derived_properties,
+                    required: derived_required,
+                    additional_properties: derived_additional_properties,
+                },
+            ) => {
+                for (name, spec_property) in spec_properties.iter() {
+                    let property_path = format!("{path}.{name}");
+                    match derived_properties.get(name) {
+                        Some(derived_property) => self.compare(
+                            spec_property,
+                            derived_property,
+                            &property_path,
+                            !spec_required.contains(name),
+                        ),
+                        None => self.mismatch(&property_path, String::from("missing property")),
+                    }
+                }
+                for name in derived_properties.keys() {
+                    if !spec_properties.contains_key(name) {
+                        self.mismatch(
+                            &format!("{path}.{name}"),
+                            String::from("unexpected property"),
+                        );
+                    }
+                }
+                for name in spec_required.symmetric_difference(&derived_required) {
+                    self.mismatch(
+                        &format!("{path}.{name}"),
+                        String::from(if spec_required.contains(name) {
+                            "expected to be required"
+                        } else {
+                            "expected to be optional"
+                        }),
+                    );
+                }
+
+                // Extra properties of types capturing unknown fields are only checked for maps
+                if spec_properties.is_empty() {
+                    match (spec_additional_properties, derived_additional_properties) {
+                        (Some(spec_values), Some(derived_values)) => {
+                            self.compare(spec_values, derived_values, &format!("{path}.*"), false)
+                        }
+                        (None, None) => {}
+                        (Some(_), None) => {
+                            self.mismatch(path, String::from("expected additional properties"))
+                        }
+                        (None, Some(_)) => {
+                            self.mismatch(path, String::from("unexpected additional properties"))
+                        }
+                    }
+                }
+            }
+            (Shape::Any, Shape::Any)
+            | (Shape::Null, Shape::Null)
+            | (Shape::Boolean, Shape::Boolean)
+            | (Shape::Integer, Shape::Integer)
+            | (Shape::Number, Shape::Number)
+            | (Shape::String, Shape::String) => {}
+            (spec_shape, derived_shape) => self.mismatch(
+                path,
+                format!(
+                    "expected {}, found {}",
+                    spec_shape.kind(),
+                    derived_shape.kind()
+                ),
+            ),
+        }
+    }
+
+    /// Matches each variant of one side with a variant of the other side.
+    fn compare_variants(
+        &mut self,
+        spec_variants: Vec<Shape>,
+        derived_variants: Vec<Shape>,
+        path: &str,
+    ) {
+        let mut unmatched_derived = derived_variants;
+
+        for (ind, spec_variant) in spec_variants.into_iter().enumerate() {
+            match unmatched_derived
+                .iter()
+                .position(|derived_variant| self.matches(&spec_variant, derived_variant))
+            {
+                Some(ind_derived) => {
+                    let derived_variant = unmatched_derived.remove(ind_derived);
+                    // Keeps recursion tracking of the matched pair
+                    self.compare_shapes(spec_variant, derived_variant, path, false);
+                }
+                None => self.mismatch(
+                    path,
+                    format!("no match for {} variant {ind}", spec_variant.kind()),
+                ),
+            }
+        }
+
+        for derived_variant in unmatched_derived.iter() {
+            self.mismatch(
+                path,
+                format!("unexpected {} variant", derived_variant.kind()),
+            );
+        }
+    }
+
+    /// Checks whether two shapes match, without recording mismatches.
+    fn matches(&self, spec_shape: &Shape, derived_shape: &Shape) -> bool {
+        let mut comparison = SchemaComparison {
+            spec: self.spec,
+            derived: self.derived,
+            visited: self.visited.clone(),
+            mismatches: vec![],
+        };
+        comparison.compare_shapes(spec_shape.clone(), derived_shape.clone(), "", false);
+        comparison.mismatches.is_empty()
+    }
+
+    fn mismatch(&mut self, path: &str, message: String) {
+        self.mismatches.push(format!("{path}: {message}"));
+    }
+}
+
+impl<'a> Shape<'a> {
+    /// Builds a union, flattening nested unions and unwrapping single variants.
+    fn union(variants: Vec<Shape<'a>>) -> Self {
+        let mut variants = variants
+            .into_iter()
+            .flat_map(|variant| match variant {
+                Shape::Union(nested) => nested,
+                variant => vec![variant],
+            })
+            .collect::<Vec<_>>();
+
+        if variants.len() == 1 {
+            variants.remove(0)
+        } else {
+            Shape::Union(variants)
+        }
+    }
+
+    /// Combines shapes that all must hold, as with `allOf` or flattened fields. Objects are merged
+    /// into every variant of unions.
+    fn merge(self, other: Shape<'a>) -> Result<Self, String> {
+        Ok(match (self, other) {
+            (Shape::Any, shape) | (shape, Shape::Any) => shape,
+            (Shape::Union(variants), shape) | (shape, Shape::Union(variants)) => Shape::union(
+                variants
+                    .into_iter()
+                    .map(|variant| variant.merge(shape.clone()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (
+                Shape::Object {
+                    mut properties,
+                    mut required,
+                    additional_properties,
+                },
+                Shape::Object {
+                    properties: other_properties,
+                    required: other_required,
+                    additional_properties: other_additional_properties,
+                },
+            ) => {
+                properties.extend(other_properties);
+                required.extend(other_required);
+                Shape::Object {
+                    properties,
+                    required,
+                    additional_properties: additional_properties.or(other_additional_properties),
+                }
+            }
+            (
+                Shape::Values(values),
+                Shape::Boolean | Shape::Integer | Shape::Number | Shape::String,
+            )
+            | (
+                Shape::Boolean | Shape::Integer | Shape::Number | Shape::String,
+                Shape::Values(values),
+            ) => Shape::Values(values),
+            (shape, other) if shape.kind() == other.kind() && !matches!(shape, Shape::Array(_)) => {
+                shape
+            }
+            (shape, other) => {
+                return Err(format!(
+                    "cannot merge {} with {}",
+                    shape.kind(),
+                    other.kind()
+                ))
+            }
+        })
+    }
+
+    /// Checks whether primitive shapes accept all the serialized `values`.
+    fn accepts(&self, values: &BTreeSet<String>) -> bool {
+        values
+            .iter()
+            .all(|value| match (self, serde_json::from_str::<Value>(value)) {
+                (Shape::Boolean, Ok(Value::Bool(_))) | (Shape::String, Ok(Value::String(_))) => {
+                    true
+                }
+                (Shape::Integer, Ok(Value::Number(number))) => !number.is_f64(),
+                (Shape::Number, Ok(Value::Number(_))) => true,
+                _ => false,
+            })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Shape::Any => "any value",
+            Shape::Null => "null",
+            Shape::Boolean => "boolean",
+            Shape::Integer => "integer",
+            Shape::Number => "number",
+            Shape::String => "string",
+            Shape::Values(_) => "fixed values",
+            Shape::Array(_) => "array",
+            Shape::Object { .. } => "object",
+            Shape::Union(_) => "union",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Compares the `ROOT` definitions of both sides, returning the mismatches found.
+    fn compare(spec_definitions: Value, derived_definitions: Value) -> Vec<String> {
+        let (Value::Object(spec_definitions), Value::Object(derived_definitions)) =
+            (spec_definitions, derived_definitions)
+        else {
+            panic!("definitions must be objects");
+        };
+
+        let spec = SchemaDocument::new(spec_definitions);
+        let derived = SchemaDocument::new(derived_definitions);
+        let mut comparison = SchemaComparison {
+            spec: &spec,
+            derived: &derived,
+            visited: HashSet::new(),
+            mismatches: vec![],
+        };
+        comparison.compare(
+            &spec.definitions["ROOT"],
+            &derived.definitions["ROOT"],
+            "ROOT",
+            false,
+        );
+
+        comparison.mismatches
+    }
+
+    #[test]
+    fn matching_schemas() {
+        let mismatches = compare(
+            json!({
+                "ROOT": {
+                    "title": "Root",
+                    "type": "object",
+                    "properties": {
+                        "hash": { "$ref": "#/components/schemas/FELT" },
+                        "tag": { "type": "string", "enum": ["A", "B"] },
+                        "note": { "type": "string" }
+                    },
+                    "required": ["hash", "tag"]
+                },
+                "FELT": { "type": "string", "pattern": "^0x[a-fA-F0-9]+$" }
+            }),
+            json!({
+                "ROOT": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "$ref": "#/$defs/Felt" },
+                        "tag": { "enum": ["A", "B"] },
+                        "note": { "type": ["string", "null"] }
+                    },
+                    "required": ["hash", "tag"]
+                },
+                "Felt": { "type": "string" }
+            }),
+        );
+
+        assert_eq!(mismatches, Vec::<String>::new());
+    }
+
+    #[test]
+    fn missing_field() {
+        let mismatches = compare(
+            json!({
+                "ROOT": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "type": "string" },
+                        "index": { "type": "integer" }
+                    },
+                    "required": ["hash", "index"]
+                }
+            }),
+            json!({
+                "ROOT": {
+                    "type": "object",
+                    "properties": {
+                        "hash": { "type": "string" }
+                    },
+                    "required": ["hash"]
+                }
+            }),
+        );
+
+        assert_eq!(
+            mismatches,
+            [
+                "ROOT.index: missing property",
+                "ROOT.index: expected to be required"
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_type() {
+        let mismatches = compare(
+            json!({
+                "ROOT": {
+                    "type": "object",
+                    "properties": {
+                        "items": { "type": "array", "items": { "type": "integer" } }
+                    },
+                    "required": ["items"]
+                }
+            }),
+            json!({
+                "ROOT": {
+                    "type": "object",
+                    "properties": {
+                        "items": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["items"]
+                }
+            }),
+        );
+
+        assert_eq!(mismatches, ["ROOT.items[]: expected integer, found string"]);
+    }
+
+    #[test]
+    fn unresolved_reference() {
+        let mismatches = compare(
+            json!({
+                "ROOT": { "$ref": "#/components/schemas/MISSING" }
+            }),
+            json!({
+                "ROOT": { "type": "string" }
+            }),
+        );
+
+        assert_eq!(
+            mismatches,
+            ["ROOT: unresolved reference #/components/schemas/MISSING"]
+        );
+    }
+}
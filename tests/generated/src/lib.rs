@@ -0,0 +1,173 @@
+//! Stand-in for the parts of `starknet-core` that generated code builds against, so that the code
+//! generated for a specification can be compiled and have its tests run outside `starknet-rs`.
+//!
+//! The generated module is written to `src/types/codegen.rs` by the `generated_code` test of the
+//! codegen tool.
+
+#![allow(dead_code)]
+
+extern crate alloc;
+
+pub mod serde {
+    pub mod byte_array {
+        pub mod base64 {
+            use alloc::{string::String, vec::Vec};
+
+            use base64::Engine;
+            use serde::{de::Error as DeError, Deserialize, Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(
+                value: &Vec<u8>,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(value))
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<u8>, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                base64::engine::general_purpose::STANDARD
+                    .decode(value)
+                    .map_err(DeError::custom)
+            }
+        }
+    }
+}
+
+pub mod types {
+    use alloc::{format, string::String};
+
+    use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    pub use starknet_types_core::felt::Felt;
+
+    /// Field elements as `0x`-prefixed hex strings.
+    pub struct UfeHex;
+
+    impl SerializeAs<Felt> for UfeHex {
+        fn serialize_as<S: Serializer>(value: &Felt, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&format!("{value:#x}"))
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Felt> for UfeHex {
+        fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Felt, D::Error> {
+            let value = String::deserialize(deserializer)?;
+            Felt::from_hex(&value).map_err(|err| DeError::custom(format!("{err:?}")))
+        }
+    }
+
+    /// Types implemented manually in `starknet-core`, which round trip any JSON value here.
+    macro_rules! manual_types {
+        ($($name:ident),* $(,)?) => {$(
+            #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+            #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+            #[serde(transparent)]
+            pub struct $name(pub serde_json::Value);
+        )*};
+    }
+
+    manual_types!(
+        BlockId,
+        BroadcastedTransaction,
+        ConfirmedBlockId,
+        ContractClass,
+        ContractExecutionError,
+        EthAddress,
+        ExecuteInvocation,
+        ExecutionResult,
+        Hash256,
+        LegacyContractAbiEntry,
+        MerkleNode,
+        ReceiptBlock,
+        Transaction,
+        TransactionContent,
+        TransactionReceipt,
+        TransactionStatus,
+        TransactionTrace,
+    );
+
+    pub mod serde_impls {
+        use alloc::{format, string::String, vec::Vec};
+        use core::fmt::LowerHex;
+
+        use serde::{
+            de::{Error as DeError, IgnoredAny},
+            Deserialize, Deserializer, Serialize, Serializer,
+        };
+        use serde_with::{DeserializeAs, SerializeAs};
+
+        /// Integers as `0x`-prefixed hex strings.
+        pub struct NumAsHex;
+
+        impl<T: LowerHex> SerializeAs<T> for NumAsHex {
+            fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&format!("{value:#x}"))
+            }
+        }
+
+        macro_rules! num_as_hex {
+            ($($num:ty),*) => {$(
+                impl<'de> DeserializeAs<'de, $num> for NumAsHex {
+                    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<$num, D::Error> {
+                        let value = String::deserialize(deserializer)?;
+                        let digits = value
+                            .strip_prefix("0x")
+                            .ok_or_else(|| DeError::custom("missing `0x` prefix"))?;
+                        <$num>::from_str_radix(digits, 16).map_err(DeError::custom)
+                    }
+                }
+            )*};
+        }
+
+        num_as_hex!(u64, u128);
+
+        /// Execution errors behind owned pointers, which serde handles the same as the errors.
+        pub struct OwnedContractExecutionError;
+
+        impl<T: Serialize> SerializeAs<T> for OwnedContractExecutionError {
+            fn serialize_as<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+                value.serialize(serializer)
+            }
+        }
+
+        impl<'de, T: Deserialize<'de>> DeserializeAs<'de, T> for OwnedContractExecutionError {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+                T::deserialize(deserializer)
+            }
+        }
+
+        /// Merkle node maps, which only ever serialize empty here as the nodes are manual types.
+        pub struct MerkleNodeMap;
+
+        impl<T> SerializeAs<T> for MerkleNodeMap {
+            fn serialize_as<S: Serializer>(_: &T, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(core::iter::empty::<()>())
+            }
+        }
+
+        impl<'de, T: Default> DeserializeAs<'de, T> for MerkleNodeMap {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+                Vec::<IgnoredAny>::deserialize(deserializer)?;
+                Ok(T::default())
+            }
+        }
+    }
+
+    mod codegen;
+
+    pub use codegen::*;
+}
+
+#[cfg(all(test, feature = "schemars"))]
+mod tests {
+    #[test]
+    fn spec_schemas_are_derived() {
+        let spec_schemas = crate::types::spec_schemas();
+        let schemas = spec_schemas["schemas"].as_object().unwrap();
+        assert!(schemas.contains_key("BROADCASTED_INVOKE_TXN"));
+        assert!(spec_schemas["$defs"].as_object().is_some());
+    }
+}
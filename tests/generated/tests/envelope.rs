@@ -0,0 +1,53 @@
+use generated_code::types::{JsonRpcId, JsonRpcPayload, JsonRpcRequest, JsonRpcResponse};
+
+fn request(json: &str) -> JsonRpcRequest {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn request_ids_are_numbers_or_strings() {
+    let with_string = request(r#"{"jsonrpc":"2.0","id":"abc","method":"starknet_chainId"}"#);
+    assert_eq!(with_string.id, Some(JsonRpcId::String(String::from("abc"))));
+
+    let with_number = request(r#"{"jsonrpc":"2.0","id":7,"method":"starknet_chainId"}"#);
+    assert_eq!(with_number.id, Some(JsonRpcId::Number(7)));
+}
+
+#[test]
+fn requests_without_ids_are_notifications() {
+    let with_null = request(r#"{"jsonrpc":"2.0","id":null,"method":"starknet_chainId"}"#);
+    assert!(with_null.is_notification());
+
+    let without = request(r#"{"jsonrpc":"2.0","method":"starknet_chainId"}"#);
+    assert!(without.is_notification());
+    assert!(!serde_json::to_string(&without).unwrap().contains("\"id\""));
+}
+
+#[test]
+fn responses_keep_null_ids() {
+    let json = r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error"}}"#;
+    let response: JsonRpcResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(response.id, None);
+    assert!(serde_json::to_string(&response)
+        .unwrap()
+        .contains("\"id\":null"));
+}
+
+#[test]
+fn batches_deserialize_params() {
+    let json = r#"[
+        {"jsonrpc":"2.0","id":1,"method":"starknet_getTransactionByHash","params":{"transaction_hash":"0x1"}},
+        {"jsonrpc":"2.0","method":"starknet_chainId"}
+    ]"#;
+    let payload: JsonRpcPayload<JsonRpcRequest> = serde_json::from_str(json).unwrap();
+    let JsonRpcPayload::Batch(batch) = payload else {
+        panic!("expected a batch");
+    };
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0].id, Some(JsonRpcId::Number(1)));
+    assert!(batch[1].is_notification());
+
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"starknet_getTransactionByHash","params":{"transaction_hash":"0x1"}}"#;
+    let payload: JsonRpcPayload<JsonRpcRequest> = serde_json::from_str(json).unwrap();
+    assert!(matches!(payload, JsonRpcPayload::Single(_)));
+}
@@ -0,0 +1,44 @@
+use std::{path::Path, process::Command};
+
+/// Generates code for the latest specification into the crate at `tests/generated`, which stands in
+/// for `starknet-core`, then builds it with its optional features and runs the tests generated
+/// along with it.
+#[test]
+#[ignore = "builds a separate crate along with the dependencies of generated code"]
+fn generated_code_passes_its_tests() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/generated");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_starknet-jsonrpc-codegen"))
+        .args([
+            "generate",
+            "--spec",
+            "0.9.0",
+            "--serde-tests",
+            "--json-schema",
+            "--validate-patterns",
+        ])
+        .output()
+        .expect("Failed to run the code generator");
+    assert!(
+        output.status.success(),
+        "Failed to generate code:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let types_dir = crate_dir.join("src/types");
+    std::fs::create_dir_all(&types_dir).expect("Failed to create module directory");
+    std::fs::write(types_dir.join("codegen.rs"), output.stdout)
+        .expect("Failed to write generated code");
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let status = Command::new(cargo)
+        .arg("test")
+        .arg("--manifest-path")
+        .arg(crate_dir.join("Cargo.toml"))
+        .args(["--features", "no_unknown_fields,validate_patterns,schemars"])
+        .status()
+        .expect("Failed to run cargo");
+    assert!(
+        status.success(),
+        "Generated code failed to build or pass its tests"
+    );
+}